@@ -0,0 +1,204 @@
+use crate::game::deviations::TablaIndices;
+use crate::game::drill::PuntosDebiles;
+use crate::game::profile::Perfiles;
+use crate::game::rules::{PerfilMesa, ReglasMesa};
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+// Fichero de configuración del usuario, p. ej. ~/.config/ratjack/config.toml
+//
+//     perfil = "atlantic_city"
+//     semilla = 1234
+//
+//     [reglas]
+//     mazos = 2
+//     banca = "H17"
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    perfil: Option<PerfilMesa>,
+    semilla: Option<u64>,
+    reglas: Option<toml::Table>,
+}
+
+// Ajustes de la partida leídos de la configuración
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ajustes {
+    pub reglas: ReglasMesa,
+    // Semilla fija para repetir siempre la misma partida
+    pub semilla: Option<u64>,
+}
+
+pub fn ruta_config() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ratjack").join("config.toml"))
+}
+
+// Tabla de índices propia del equipo, con el mismo formato que data/indices.toml
+pub fn ruta_indices() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ratjack").join("indices.toml"))
+}
+
+// Historial de manos, una ronda por línea, p. ej. ~/.local/share/ratjack/historial.jsonl
+pub fn ruta_historial() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("ratjack").join("historial.jsonl"))
+}
+
+// Perfiles de jugador con sus estadísticas de siempre
+pub fn ruta_perfiles() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("ratjack").join("perfiles.toml"))
+}
+
+// Aciertos del modo práctica en cada situación, para repasar los puntos débiles
+pub fn ruta_practica() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("ratjack").join("practica.toml"))
+}
+
+// Partida a medias que se guarda al salir para seguirla más tarde
+pub fn ruta_partida() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("ratjack").join("partida.json"))
+}
+
+// Lee la configuración del usuario; sin fichero se juega con las reglas por defecto
+pub fn cargar_config() -> Result<Ajustes, String> {
+    let Some(ruta) = ruta_config().filter(|ruta| ruta.exists()) else {
+        return config_desde_toml("");
+    };
+    let texto = fs::read_to_string(&ruta)
+        .map_err(|e| format!("No se pudo leer {}: {}", ruta.display(), e))?;
+    config_desde_toml(&texto).map_err(|e| format!("Error en {}: {}", ruta.display(), e))
+}
+
+// Lee la tabla de índices del usuario; sin fichero se usan los incluidos en el juego
+pub fn cargar_indices() -> Result<TablaIndices, String> {
+    let Some(ruta) = ruta_indices().filter(|ruta| ruta.exists()) else {
+        return Ok(TablaIndices::default());
+    };
+    let texto = fs::read_to_string(&ruta)
+        .map_err(|e| format!("No se pudo leer {}: {}", ruta.display(), e))?;
+    TablaIndices::desde_toml(&texto).map_err(|e| format!("Error en {}: {}", ruta.display(), e))
+}
+
+// Lee los perfiles guardados; sin fichero todavía no hay ninguno
+pub fn cargar_perfiles() -> Result<Perfiles, String> {
+    let Some(ruta) = ruta_perfiles().filter(|ruta| ruta.exists()) else {
+        return Ok(Perfiles::default());
+    };
+    let texto = fs::read_to_string(&ruta)
+        .map_err(|e| format!("No se pudo leer {}: {}", ruta.display(), e))?;
+    Perfiles::desde_toml(&texto).map_err(|e| format!("Error en {}: {}", ruta.display(), e))
+}
+
+pub fn guardar_perfiles(perfiles: &Perfiles) -> Result<(), String> {
+    let ruta = ruta_perfiles().ok_or("No hay carpeta de datos para los perfiles")?;
+    if let Some(carpeta) = ruta.parent() {
+        fs::create_dir_all(carpeta).map_err(|e| e.to_string())?;
+    }
+    fs::write(&ruta, perfiles.a_toml()?)
+        .map_err(|e| format!("No se pudo guardar {}: {}", ruta.display(), e))
+}
+
+// Sin fichero todavía no se ha practicado ninguna situación
+pub fn cargar_practica() -> Result<PuntosDebiles, String> {
+    let Some(ruta) = ruta_practica().filter(|ruta| ruta.exists()) else {
+        return Ok(PuntosDebiles::default());
+    };
+    let texto = fs::read_to_string(&ruta)
+        .map_err(|e| format!("No se pudo leer {}: {}", ruta.display(), e))?;
+    PuntosDebiles::desde_toml(&texto).map_err(|e| format!("Error en {}: {}", ruta.display(), e))
+}
+
+pub fn guardar_practica(puntos: &PuntosDebiles) -> Result<(), String> {
+    let ruta = ruta_practica().ok_or("No hay carpeta de datos para la práctica")?;
+    if let Some(carpeta) = ruta.parent() {
+        fs::create_dir_all(carpeta).map_err(|e| e.to_string())?;
+    }
+    fs::write(&ruta, puntos.a_toml()?)
+        .map_err(|e| format!("No se pudo guardar {}: {}", ruta.display(), e))
+}
+
+pub fn config_desde_toml(texto: &str) -> Result<Ajustes, String> {
+    let config: Config = toml::from_str(texto).map_err(|e| e.to_string())?;
+    Ok(Ajustes {
+        reglas: reglas_de_config(config.perfil, config.reglas)?,
+        semilla: config.semilla,
+    })
+}
+
+// Parte del perfil indicado (o del por defecto) y aplica encima las reglas personalizadas
+fn reglas_de_config(
+    perfil: Option<PerfilMesa>,
+    personalizadas: Option<toml::Table>,
+) -> Result<ReglasMesa, String> {
+    let base = perfil.map(|p| p.reglas()).unwrap_or_default();
+    let Some(personalizadas) = personalizadas else {
+        return Ok(base);
+    };
+
+    let mut reglas = toml::Table::try_from(&base).map_err(|e| e.to_string())?;
+    if !personalizadas.contains_key("nombre") {
+        reglas.insert("nombre".to_string(), "Personalizada".into());
+    }
+    reglas.extend(personalizadas);
+    let reglas: ReglasMesa = reglas
+        .try_into()
+        .map_err(|e: toml::de::Error| e.to_string())?;
+    reglas.validar()?;
+    Ok(reglas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::{PagoBlackjack, ReglaBanca};
+
+    fn reglas_desde_toml(texto: &str) -> Result<ReglasMesa, String> {
+        config_desde_toml(texto).map(|ajustes| ajustes.reglas)
+    }
+
+    #[test]
+    fn test_config_vacia() {
+        assert_eq!(reglas_desde_toml("").unwrap(), ReglasMesa::default());
+    }
+
+    #[test]
+    fn test_config_perfil() {
+        let reglas = reglas_desde_toml("perfil = \"europea\"").unwrap();
+        assert_eq!(reglas, PerfilMesa::Europea.reglas());
+    }
+
+    #[test]
+    fn test_config_personalizada() {
+        let texto = r#"
+            perfil = "downtown"
+
+            [reglas]
+            mazos = 2
+            pago_blackjack = "6:5"
+        "#;
+        let reglas = reglas_desde_toml(texto).unwrap();
+        assert_eq!(reglas.nombre, "Personalizada");
+        assert_eq!(reglas.mazos, 2);
+        assert_eq!(reglas.pago_blackjack, PagoBlackjack::SeisACinco);
+        // El resto se hereda del perfil
+        assert_eq!(reglas.banca, ReglaBanca::H17);
+    }
+
+    #[test]
+    fn test_config_semilla() {
+        assert_eq!(config_desde_toml("").unwrap().semilla, None);
+        let ajustes = config_desde_toml("semilla = 1234\nperfil = \"downtown\"").unwrap();
+        assert_eq!(ajustes.semilla, Some(1234));
+        assert_eq!(ajustes.reglas, PerfilMesa::Downtown.reglas());
+        assert!(config_desde_toml("semilla = -1").is_err());
+    }
+
+    #[test]
+    fn test_config_invalida() {
+        assert!(reglas_desde_toml("perfil = \"montecarlo\"").is_err());
+        assert!(reglas_desde_toml("[reglas]\nmazos = \"seis\"").is_err());
+        assert!(reglas_desde_toml("[reglas]\nbarajas = 6").is_err());
+        assert!(reglas_desde_toml("[reglas]\nmazos = 0").is_err());
+        assert!(reglas_desde_toml("[reglas]\napuesta_minima = 600").is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::game::ev::{permite_doblar, Evaluador};
+use crate::game::mistakes::Categoria;
+use crate::game::rules::{ReglasMesa, Rendicion};
+use crate::game::strategy::{accion_basica, Accion, Opciones, Situacion};
+
+// Cartas visibles de la banca en las columnas de la tabla; el as cuenta como 11
+pub const CARTAS_BANCA: [u8; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+// Filas de cada tabla: el total de la mano o, en las parejas, la carta repetida
+pub fn filas(categoria: Categoria) -> Vec<u8> {
+    match categoria {
+        Categoria::Duras => (5..=20).collect(),
+        Categoria::Blandas => (13..=20).collect(),
+        Categoria::Parejas => (2..=11).collect(),
+    }
+}
+
+// Nombre de la fila como aparece en la tabla, p. ej. "16", "A7" o "8-8"
+pub fn nombre_fila(categoria: Categoria, fila: u8) -> String {
+    let carta = |valor: u8| match valor {
+        11 => "A".to_string(),
+        valor => valor.to_string(),
+    };
+    match categoria {
+        Categoria::Duras => fila.to_string(),
+        Categoria::Blandas => format!("A{}", fila - 11),
+        Categoria::Parejas => format!("{}-{}", carta(fila), carta(fila)),
+    }
+}
+
+pub fn situacion(categoria: Categoria, fila: u8, banca: u8) -> Situacion {
+    match categoria {
+        Categoria::Duras => Situacion {
+            total: fila,
+            blanda: false,
+            par: None,
+            banca,
+        },
+        Categoria::Blandas => Situacion {
+            total: fila,
+            blanda: true,
+            par: None,
+            banca,
+        },
+        Categoria::Parejas => Situacion {
+            total: if fila == 11 { 12 } else { fila * 2 },
+            blanda: fila == 11,
+            par: Some(fila),
+            banca,
+        },
+    }
+}
+
+// Jugadas que permiten las reglas con las dos primeras cartas
+pub fn opciones(situacion: &Situacion, reglas: &ReglasMesa) -> Opciones {
+    Opciones {
+        doblar: permite_doblar(situacion.total, reglas),
+        dividir: situacion.par.is_some() && reglas.max_manos > 1,
+        rendirse: reglas.rendicion != Rendicion::No,
+    }
+}
+
+// Jugada de una casilla y, si es doblar o rendirse, la que se hace cuando no se puede
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Casilla {
+    pub accion: Accion,
+    pub alternativa: Option<Accion>,
+}
+
+impl Casilla {
+    pub fn nueva(situacion: &Situacion, reglas: &ReglasMesa) -> Self {
+        let opciones = opciones(situacion, reglas);
+        let accion = accion_basica(*situacion, reglas, opciones);
+        let sin = match accion {
+            Accion::Doblar => Opciones {
+                doblar: false,
+                ..opciones
+            },
+            Accion::Rendirse => Opciones {
+                rendirse: false,
+                ..opciones
+            },
+            _ => {
+                return Casilla {
+                    accion,
+                    alternativa: None,
+                }
+            }
+        };
+        Casilla {
+            accion,
+            alternativa: Some(accion_basica(*situacion, reglas, sin)),
+        }
+    }
+
+    // Texto de la casilla, p. ej. "H", "Ds" (doblar o plantarse) o "Rh"
+    pub fn texto(&self) -> String {
+        let mut texto = self.accion.letra().to_string();
+        if let Some(alternativa) = self.alternativa {
+            texto.push(alternativa.letra().to_ascii_lowercase());
+        }
+        texto
+    }
+}
+
+// Valor esperado de cada jugada permitida, de mejor a peor, con un shoe infinito
+pub fn jugadas(situacion: &Situacion, reglas: &ReglasMesa) -> Vec<(Accion, f64)> {
+    let opciones = opciones(situacion, reglas);
+    let mut evaluador = Evaluador::nuevo(situacion.banca, reglas);
+    let mut jugadas: Vec<(Accion, f64)> = [
+        (Accion::Pedir, true),
+        (Accion::Plantarse, true),
+        (Accion::Doblar, opciones.doblar),
+        (Accion::Dividir, opciones.dividir),
+        (Accion::Rendirse, opciones.rendirse),
+    ]
+    .into_iter()
+    .filter(|&(_, permitida)| permitida)
+    .map(|(accion, _)| (accion, evaluador.ev(situacion, accion)))
+    .collect();
+    jugadas.sort_by(|a, b| b.1.total_cmp(&a.1));
+    jugadas
+}
+
+// Casilla elegida en el visor: tabla, fila y columna
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CursorTabla {
+    pub tabla: usize,
+    pub fila: usize,
+    pub columna: usize,
+}
+
+impl CursorTabla {
+    pub fn categoria(&self) -> Categoria {
+        Categoria::TODAS[self.tabla]
+    }
+
+    pub fn situacion(&self) -> Situacion {
+        let categoria = self.categoria();
+        situacion(
+            categoria,
+            filas(categoria)[self.fila],
+            CARTAS_BANCA[self.columna],
+        )
+    }
+
+    pub fn mover_fila(&mut self, arriba: bool) {
+        let ultima = filas(self.categoria()).len() - 1;
+        self.fila = if arriba {
+            self.fila.saturating_sub(1)
+        } else {
+            (self.fila + 1).min(ultima)
+        };
+    }
+
+    // Al pasar del borde de una tabla se salta a la de al lado
+    pub fn mover_columna(&mut self, izquierda: bool) {
+        let ultima = CARTAS_BANCA.len() - 1;
+        match (izquierda, self.columna) {
+            (true, 0) if self.tabla > 0 => {
+                self.tabla -= 1;
+                self.columna = ultima;
+            }
+            (false, columna) if columna == ultima && self.tabla + 1 < Categoria::TODAS.len() => {
+                self.tabla += 1;
+                self.columna = 0;
+            }
+            (true, columna) => self.columna = columna.saturating_sub(1),
+            (false, columna) => self.columna = (columna + 1).min(ultima),
+        }
+        self.fila = self.fila.min(filas(self.categoria()).len() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::{PerfilMesa, ReglaDoblar};
+
+    fn casilla(categoria: Categoria, fila: u8, banca: u8, reglas: &ReglasMesa) -> String {
+        Casilla::nueva(&situacion(categoria, fila, banca), reglas).texto()
+    }
+
+    #[test]
+    fn test_casillas() {
+        let reglas = PerfilMesa::VegasStrip.reglas();
+        assert_eq!(casilla(Categoria::Duras, 16, 10, &reglas), "Rh");
+        assert_eq!(casilla(Categoria::Duras, 12, 4, &reglas), "S");
+        assert_eq!(casilla(Categoria::Duras, 11, 6, &reglas), "Dh");
+        assert_eq!(casilla(Categoria::Blandas, 18, 4, &reglas), "Ds");
+        assert_eq!(casilla(Categoria::Parejas, 8, 9, &reglas), "P");
+        assert_eq!(casilla(Categoria::Parejas, 5, 6, &reglas), "Dh");
+        assert_eq!(nombre_fila(Categoria::Blandas, 18), "A7");
+        assert_eq!(nombre_fila(Categoria::Parejas, 11), "A-A");
+
+        // La tabla sigue a las reglas de la mesa
+        let sin_rendicion = ReglasMesa {
+            rendicion: Rendicion::No,
+            doblar: ReglaDoblar::DiezAOnce,
+            ..reglas.clone()
+        };
+        assert_eq!(casilla(Categoria::Duras, 16, 10, &sin_rendicion), "H");
+        assert_eq!(casilla(Categoria::Blandas, 18, 4, &sin_rendicion), "S");
+    }
+
+    #[test]
+    fn test_jugadas() {
+        let reglas = PerfilMesa::VegasStrip.reglas();
+        let situacion = situacion(Categoria::Parejas, 8, 10);
+        let jugadas = jugadas(&situacion, &reglas);
+        let acciones: Vec<Accion> = jugadas.iter().map(|&(accion, _)| accion).collect();
+        assert_eq!(acciones.len(), 5);
+        assert!(jugadas.windows(2).all(|par| par[0].1 >= par[1].1));
+        // La mejor jugada coincide con la de la tabla
+        assert_eq!(jugadas[0].0, Casilla::nueva(&situacion, &reglas).accion);
+    }
+
+    #[test]
+    fn test_tablas_de_cada_mesa() {
+        // Con varios mazos, cada casilla queda a menos de una centésima de la mejor
+        // jugada del shoe infinito; solo se separan en dobles blandos muy ajustados
+        for perfil in [
+            PerfilMesa::VegasStrip,
+            PerfilMesa::AtlanticCity,
+            PerfilMesa::Europea,
+        ] {
+            let reglas = perfil.reglas();
+            for categoria in Categoria::TODAS {
+                for fila in filas(categoria) {
+                    for banca in CARTAS_BANCA {
+                        let situacion = situacion(categoria, fila, banca);
+                        let jugadas = jugadas(&situacion, &reglas);
+                        let accion = Casilla::nueva(&situacion, &reglas).accion;
+                        let ev = jugadas.iter().find(|&&(jugada, _)| jugada == accion);
+                        assert!(
+                            ev.is_some_and(|&(_, ev)| jugadas[0].1 - ev < 0.01),
+                            "{}: {} con {}",
+                            perfil.nombre(),
+                            situacion.descripcion(),
+                            accion.nombre()
+                        );
+                    }
+                }
+            }
+        }
+
+        // Con un mazo el shoe infinito no sirve de referencia: se comprueba la tabla
+        let reglas = PerfilMesa::Downtown.reglas();
+        assert_eq!(casilla(Categoria::Duras, 8, 6, &reglas), "Dh");
+        assert_eq!(casilla(Categoria::Duras, 11, 11, &reglas), "Dh");
+        assert_eq!(casilla(Categoria::Blandas, 19, 6, &reglas), "Ds");
+        assert_eq!(casilla(Categoria::Parejas, 7, 10, &reglas), "S");
+        assert_eq!(casilla(Categoria::Parejas, 3, 3, &reglas), "H");
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut cursor = CursorTabla::default();
+        cursor.mover_columna(true);
+        assert_eq!(cursor, CursorTabla::default());
+
+        for _ in 0..20 {
+            cursor.mover_fila(false);
+        }
+        assert_eq!(cursor.situacion().total, 20);
+        for _ in 0..10 {
+            cursor.mover_columna(false);
+        }
+        // Al pasar a los blandos la fila se ajusta a la última que hay
+        assert_eq!(cursor.categoria(), Categoria::Blandas);
+        assert_eq!((cursor.fila, cursor.columna), (7, 0));
+        assert_eq!(cursor.situacion().total, 20);
+    }
+}
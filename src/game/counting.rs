@@ -0,0 +1,201 @@
+use crate::game::deck::Carta;
+use serde::{Deserialize, Serialize};
+
+// Sistemas de conteo de cartas con sus tablas de valores
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SistemaConteo {
+    #[default]
+    HiLo,
+    Ko,
+    HiOptI,
+    OmegaII,
+    Zen,
+}
+
+impl SistemaConteo {
+    pub const TODOS: [SistemaConteo; 5] = [
+        SistemaConteo::HiLo,
+        SistemaConteo::Ko,
+        SistemaConteo::HiOptI,
+        SistemaConteo::OmegaII,
+        SistemaConteo::Zen,
+    ];
+
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            SistemaConteo::HiLo => "Hi-Lo",
+            SistemaConteo::Ko => "KO",
+            SistemaConteo::HiOptI => "Hi-Opt I",
+            SistemaConteo::OmegaII => "Omega II",
+            SistemaConteo::Zen => "Zen",
+        }
+    }
+
+    // Valor que suma o resta cada carta a la cuenta
+    pub fn valor(&self, carta: &Carta) -> i32 {
+        self.valor_puntos(carta.puntos())
+    }
+
+    // Valor de una carta según sus puntos, con el as como 11
+    pub fn valor_puntos(&self, puntos: u8) -> i32 {
+        match self {
+            SistemaConteo::HiLo => match puntos {
+                2..=6 => 1,
+                7..=9 => 0,
+                _ => -1,
+            },
+            SistemaConteo::Ko => match puntos {
+                2..=7 => 1,
+                8 | 9 => 0,
+                _ => -1,
+            },
+            SistemaConteo::HiOptI => match puntos {
+                3..=6 => 1,
+                10 => -1,
+                _ => 0,
+            },
+            SistemaConteo::OmegaII => match puntos {
+                2 | 3 | 7 => 1,
+                4..=6 => 2,
+                9 => -1,
+                10 => -2,
+                _ => 0,
+            },
+            SistemaConteo::Zen => match puntos {
+                2 | 3 | 7 => 1,
+                4..=6 => 2,
+                10 => -2,
+                11 => -1,
+                _ => 0,
+            },
+        }
+    }
+
+    // En un sistema balanceado la cuenta de todo el shoe suma cero
+    pub fn balanceado(&self) -> bool {
+        !matches!(self, SistemaConteo::Ko)
+    }
+
+    // KO empieza en negativo para que su cuenta corriente haga de cuenta real
+    pub fn cuenta_inicial(&self, mazos: u8) -> i32 {
+        if self.balanceado() {
+            0
+        } else {
+            4 - 4 * mazos as i32
+        }
+    }
+
+    pub fn siguiente(&self) -> SistemaConteo {
+        let posicion = Self::TODOS.iter().position(|s| s == self).unwrap_or(0);
+        Self::TODOS[(posicion + 1) % Self::TODOS.len()]
+    }
+}
+
+// Cuenta corriente según cuántas cartas de cada valor, del 2 al as, se han visto
+// desde el último barajado
+pub fn cuenta_corriente(sistema: SistemaConteo, vistas: &[u32; 10], mazos: u8) -> i32 {
+    (2..=11).zip(vistas).fold(
+        sistema.cuenta_inicial(mazos),
+        |cuenta, (puntos, &cartas)| cuenta + sistema.valor_puntos(puntos) * cartas as i32,
+    )
+}
+
+// Cuenta real: cuenta corriente por cada mazo que queda en el shoe
+pub fn cuenta_real(cuenta: i32, cartas_restantes: usize) -> f64 {
+    let mazos_restantes = (cartas_restantes as f64 / 52.0).max(0.5);
+    cuenta as f64 / mazos_restantes
+}
+
+// Cuenta con la que se comparan los índices: la real en sistemas balanceados
+// y la corriente en los no balanceados como KO
+pub fn cuenta_indices(sistema: SistemaConteo, cuenta: i32, cartas_restantes: usize) -> f64 {
+    if sistema.balanceado() {
+        cuenta_real(cuenta, cartas_restantes)
+    } else {
+        cuenta as f64
+    }
+}
+
+// Aciertos en las preguntas sobre la cuenta
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuntuacionConteo {
+    pub preguntas: u32,
+    pub aciertos: u32,
+}
+
+impl PuntuacionConteo {
+    pub fn responder(&mut self, respuesta: i32, cuenta: i32) -> bool {
+        self.preguntas += 1;
+        let acierto = respuesta == cuenta;
+        if acierto {
+            self.aciertos += 1;
+        }
+        acierto
+    }
+
+    pub fn precision(&self) -> f64 {
+        if self.preguntas == 0 {
+            0.0
+        } else {
+            self.aciertos as f64 / self.preguntas as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::deck::cartas_mazo;
+
+    #[test]
+    fn test_sistemas_balanceados() {
+        for sistema in SistemaConteo::TODOS {
+            let mut vistas = [0; 10];
+            for carta in cartas_mazo() {
+                vistas[carta.puntos() as usize - 2] += 1;
+            }
+            let total = cuenta_corriente(sistema, &vistas, 1);
+            if sistema.balanceado() {
+                assert_eq!(total, 0, "{}", sistema.nombre());
+            } else {
+                // KO suma +4 por mazo, compensado por la cuenta inicial
+                assert_eq!(total, 4, "{}", sistema.nombre());
+            }
+        }
+    }
+
+    #[test]
+    fn test_valores() {
+        let carta = |valor| Carta {
+            valor,
+            palo: crate::game::deck::Palo::Picas,
+        };
+        assert_eq!(SistemaConteo::HiLo.valor(&carta(5)), 1);
+        assert_eq!(SistemaConteo::HiLo.valor(&carta(13)), -1);
+        assert_eq!(SistemaConteo::Ko.valor(&carta(7)), 1);
+        assert_eq!(SistemaConteo::HiOptI.valor(&carta(1)), 0);
+        assert_eq!(SistemaConteo::OmegaII.valor(&carta(10)), -2);
+        assert_eq!(SistemaConteo::Zen.valor(&carta(1)), -1);
+    }
+
+    #[test]
+    fn test_cuenta_real() {
+        assert_eq!(SistemaConteo::Ko.cuenta_inicial(6), -20);
+        assert_eq!(cuenta_real(6, 156), 2.0);
+        assert_eq!(cuenta_real(-3, 26), -6.0);
+        assert_eq!(cuenta_real(3, 0), 6.0);
+
+        assert_eq!(cuenta_indices(SistemaConteo::HiLo, 5, 26), 10.0);
+        assert_eq!(cuenta_indices(SistemaConteo::Ko, -3, 26), -3.0);
+    }
+
+    #[test]
+    fn test_puntuacion() {
+        let mut puntuacion = PuntuacionConteo::default();
+        assert!(puntuacion.responder(3, 3));
+        assert!(!puntuacion.responder(2, -1));
+        assert_eq!(puntuacion.precision(), 0.5);
+        assert_eq!(SistemaConteo::Zen.siguiente(), SistemaConteo::HiLo);
+    }
+}
@@ -1,20 +1,27 @@
-use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Palo {
     Corazones,
     Diamantes,
+    #[serde(rename = "treboles")]
     Tréboles,
     Picas,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Carta {
     pub valor: u8,
     pub palo: Palo,
 }
 
 impl Carta {
+    // Del as (1) al rey (13); cualquier otro valor solo puede venir de un fichero editado
+    pub fn es_valida(&self) -> bool {
+        (1..=13).contains(&self.valor)
+    }
+
     pub fn simbolo(&self) -> char {
         match self.palo {
             Palo::Corazones => '♡',
@@ -27,7 +34,7 @@ impl Carta {
     pub fn puntos(&self) -> u8 {
         match self.valor {
             1 => 11,
-            11 | 12 | 13 => 10,
+            11..=13 => 10,
             _ => self.valor,
         }
     }
@@ -43,8 +50,8 @@ impl Carta {
     }
 }
 
-// Función para crear una baraja nueva barajada
-pub fn crear_baraja() -> Vec<Carta> {
+// Las 52 cartas de un mazo, ordenadas
+pub fn cartas_mazo() -> Vec<Carta> {
     let palos = [
         Palo::Corazones,
         Palo::Diamantes,
@@ -58,8 +65,6 @@ pub fn crear_baraja() -> Vec<Carta> {
             baraja.push(Carta { valor, palo });
         }
     }
-
-    baraja.shuffle(&mut rand::thread_rng());
     baraja
 }
 
@@ -68,14 +73,14 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_crear_baraja_length() {
-        let baraja = crear_baraja();
+    fn test_cartas_mazo_length() {
+        let baraja = cartas_mazo();
         assert_eq!(baraja.len(), 52);
     }
 
     #[test]
-    fn test_crear_baraja_unique_cards() {
-        let baraja = crear_baraja();
+    fn test_cartas_mazo_unique_cards() {
+        let baraja = cartas_mazo();
         let mut unique_cards = Vec::new();
 
         for card in &baraja {
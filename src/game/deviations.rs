@@ -0,0 +1,325 @@
+use crate::game::counting::SistemaConteo;
+use crate::game::rules::ReglasMesa;
+use crate::game::strategy::{accion_basica, Accion, Opciones, Situacion};
+use serde::Deserialize;
+
+// Tabla de índices incluida con el juego
+pub const INDICES_POR_DEFECTO: &str = include_str!("../../data/indices.toml");
+
+// Mano a la que se aplica un índice: "16", "A7" o "10-10"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ManoIndice {
+    Dura(u8),
+    Blanda(u8),
+    Pareja(u8),
+}
+
+impl TryFrom<String> for ManoIndice {
+    type Error = String;
+
+    fn try_from(texto: String) -> Result<Self, Self::Error> {
+        let error = || format!("Mano no válida: {}", texto);
+        if let Some((a, b)) = texto.split_once('-') {
+            let carta = valor_carta(a).ok_or_else(error)?;
+            if valor_carta(b) != Some(carta) {
+                return Err(error());
+            }
+            return Ok(ManoIndice::Pareja(carta));
+        }
+        if let Some(resto) = texto.strip_prefix('A') {
+            let carta = resto.parse::<u8>().map_err(|_| error())?;
+            return match carta {
+                2..=9 => Ok(ManoIndice::Blanda(11 + carta)),
+                _ => Err(error()),
+            };
+        }
+        match texto.parse::<u8>() {
+            Ok(total @ 4..=21) => Ok(ManoIndice::Dura(total)),
+            _ => Err(error()),
+        }
+    }
+}
+
+// Carta visible de la banca: "2".."10" o "A" (que vale 11)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct CartaBanca(pub u8);
+
+impl TryFrom<String> for CartaBanca {
+    type Error = String;
+
+    fn try_from(texto: String) -> Result<Self, Self::Error> {
+        valor_carta(&texto)
+            .map(CartaBanca)
+            .ok_or_else(|| format!("Carta de la banca no válida: {}", texto))
+    }
+}
+
+fn valor_carta(texto: &str) -> Option<u8> {
+    match texto {
+        "A" => Some(11),
+        _ => texto.parse().ok().filter(|valor| (2..=10).contains(valor)),
+    }
+}
+
+// Jugada que se desvía de la estrategia básica según la cuenta
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Indice {
+    pub mano: ManoIndice,
+    pub banca: CartaBanca,
+    pub accion: Accion,
+    pub indice: f64,
+    // La acción se juega por debajo del índice en lugar de a partir de él
+    #[serde(default)]
+    pub menor: bool,
+}
+
+impl Indice {
+    pub fn aplica(&self, situacion: &Situacion) -> bool {
+        if self.banca.0 != situacion.banca {
+            return false;
+        }
+        match self.mano {
+            ManoIndice::Pareja(carta) => situacion.par == Some(carta),
+            ManoIndice::Blanda(total) => situacion.blanda && situacion.total == total,
+            ManoIndice::Dura(total) => {
+                !situacion.blanda && situacion.par.is_none() && situacion.total == total
+            }
+        }
+    }
+
+    pub fn se_cumple(&self, cuenta: f64) -> bool {
+        if self.menor {
+            cuenta < self.indice
+        } else {
+            cuenta >= self.indice
+        }
+    }
+
+    // Descripción corta, p. ej. "16 vs 10 en +0"
+    pub fn descripcion(&self) -> String {
+        let carta = |valor: u8| match valor {
+            11 => "A".to_string(),
+            valor => valor.to_string(),
+        };
+        let mano = match self.mano {
+            ManoIndice::Dura(total) => total.to_string(),
+            ManoIndice::Blanda(total) => format!("A{}", total - 11),
+            ManoIndice::Pareja(valor) => format!("{}-{}", carta(valor), carta(valor)),
+        };
+        let signo = if self.menor { "<" } else { "≥" };
+        format!(
+            "{} vs {} con cuenta {} {:+}",
+            mano,
+            carta(self.banca.0),
+            signo,
+            self.indice
+        )
+    }
+}
+
+// Índices de un sistema de conteo, leídos de un fichero TOML
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TablaIndices {
+    #[serde(default)]
+    pub sistema: SistemaConteo,
+    // Cuenta a partir de la que conviene asegurar
+    pub seguro: Option<f64>,
+    #[serde(default, rename = "jugada")]
+    pub jugadas: Vec<Indice>,
+}
+
+impl TablaIndices {
+    pub fn desde_toml(texto: &str) -> Result<Self, String> {
+        toml::from_str(texto).map_err(|e| e.to_string())
+    }
+
+    // Jugada recomendada con la cuenta actual y el índice que la justifica, si lo hay
+    pub fn recomendar(
+        &self,
+        situacion: Situacion,
+        reglas: &ReglasMesa,
+        opciones: Opciones,
+        cuenta: f64,
+    ) -> (Accion, Option<&Indice>) {
+        let basica = accion_basica(situacion, reglas, opciones);
+        let permitida = |accion: Accion| match accion {
+            Accion::Pedir | Accion::Plantarse => true,
+            Accion::Doblar => opciones.doblar,
+            Accion::Dividir => opciones.dividir,
+            Accion::Rendirse => opciones.rendirse,
+        };
+
+        let indice = self.jugadas.iter().find(|indice| {
+            indice.aplica(&situacion)
+                && permitida(indice.accion)
+                // Una rendición de la estrategia básica solo la cambia otro índice de
+                // rendición o uno que dice qué hacer por debajo del índice
+                && (basica != Accion::Rendirse
+                    || indice.accion == Accion::Rendirse
+                    || indice.menor)
+                && indice.se_cumple(cuenta)
+        });
+        match indice {
+            Some(indice) => (indice.accion, Some(indice)),
+            None => (basica, None),
+        }
+    }
+
+    pub fn tomar_seguro(&self, cuenta: f64) -> bool {
+        self.seguro.is_some_and(|indice| cuenta >= indice)
+    }
+}
+
+impl Default for TablaIndices {
+    fn default() -> Self {
+        TablaIndices::desde_toml(INDICES_POR_DEFECTO).expect("data/indices.toml no es válido")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::ReglaBanca;
+
+    const TODAS: Opciones = Opciones {
+        doblar: true,
+        dividir: true,
+        rendirse: false,
+    };
+
+    fn dura(total: u8, banca: u8) -> Situacion {
+        Situacion {
+            total,
+            blanda: false,
+            par: None,
+            banca,
+        }
+    }
+
+    #[test]
+    fn test_tabla_por_defecto() {
+        let tabla = TablaIndices::default();
+        assert_eq!(tabla.sistema, SistemaConteo::HiLo);
+        assert_eq!(tabla.seguro, Some(3.0));
+        assert_eq!(tabla.jugadas.len(), 23);
+        assert!(tabla.tomar_seguro(3.0));
+        assert!(!tabla.tomar_seguro(2.9));
+    }
+
+    #[test]
+    fn test_illustrious_18() {
+        let tabla = TablaIndices::default();
+        let reglas = ReglasMesa::default();
+
+        let (accion, indice) = tabla.recomendar(dura(16, 10), &reglas, TODAS, 0.5);
+        assert_eq!(accion, Accion::Plantarse);
+        assert_eq!(indice.unwrap().descripcion(), "16 vs 10 con cuenta ≥ +0");
+        assert_eq!(
+            tabla.recomendar(dura(16, 10), &reglas, TODAS, -1.0),
+            (Accion::Pedir, None)
+        );
+
+        let (accion, _) = tabla.recomendar(dura(12, 4), &reglas, TODAS, -0.5);
+        assert_eq!(accion, Accion::Pedir);
+
+        let diez = Situacion {
+            total: 20,
+            blanda: false,
+            par: Some(10),
+            banca: 6,
+        };
+        assert_eq!(
+            tabla.recomendar(diez, &reglas, TODAS, 4.0).0,
+            Accion::Dividir
+        );
+        assert_eq!(
+            tabla.recomendar(diez, &reglas, TODAS, 3.0).0,
+            Accion::Plantarse
+        );
+    }
+
+    #[test]
+    fn test_fab_4() {
+        let tabla = TablaIndices::default();
+        let reglas = ReglasMesa::default();
+        let con_rendicion = Opciones {
+            rendirse: true,
+            ..TODAS
+        };
+
+        assert_eq!(
+            tabla
+                .recomendar(dura(14, 10), &reglas, con_rendicion, 3.0)
+                .0,
+            Accion::Rendirse
+        );
+        // La rendición básica de 16 contra 10 no la cambia el índice de plantarse
+        assert_eq!(
+            tabla
+                .recomendar(dura(16, 10), &reglas, con_rendicion, 1.0)
+                .0,
+            Accion::Rendirse
+        );
+        // La básica se rinde con 15 contra 10, pero por debajo de 0 se pide
+        assert_eq!(
+            tabla
+                .recomendar(dura(15, 10), &reglas, con_rendicion, 0.0)
+                .0,
+            Accion::Rendirse
+        );
+        let (accion, indice) = tabla.recomendar(dura(15, 10), &reglas, con_rendicion, -1.0);
+        assert_eq!(accion, Accion::Pedir);
+        assert_eq!(indice.unwrap().descripcion(), "15 vs 10 con cuenta < +0");
+
+        // Con H17 también se rinde con 15 contra un as, salvo por debajo de +1
+        let h17 = ReglasMesa {
+            banca: ReglaBanca::H17,
+            ..ReglasMesa::default()
+        };
+        assert_eq!(
+            tabla.recomendar(dura(15, 11), &h17, con_rendicion, 2.0).0,
+            Accion::Rendirse
+        );
+        assert_eq!(
+            tabla.recomendar(dura(15, 11), &h17, con_rendicion, 0.0).0,
+            Accion::Pedir
+        );
+
+        // Sin rendición, 15 contra 10 se planta a partir de +4
+        assert_eq!(
+            tabla.recomendar(dura(15, 10), &reglas, TODAS, 4.0).0,
+            Accion::Plantarse
+        );
+    }
+
+    #[test]
+    fn test_tabla_personalizada() {
+        let texto = r#"
+            [[jugada]]
+            mano = "A8"
+            banca = "6"
+            accion = "doblar"
+            indice = 1
+        "#;
+        let tabla = TablaIndices::desde_toml(texto).unwrap();
+        assert_eq!(tabla.seguro, None);
+        assert_eq!(tabla.jugadas[0].mano, ManoIndice::Blanda(19));
+
+        assert!(TablaIndices::desde_toml(
+            "[[jugada]]\nmano = \"23\"\nbanca = \"6\"\naccion = \"pedir\"\nindice = 1"
+        )
+        .is_err());
+        assert!(TablaIndices::desde_toml(
+            "[[jugada]]\nmano = \"8-9\"\nbanca = \"6\"\naccion = \"pedir\"\nindice = 1"
+        )
+        .is_err());
+        assert!(TablaIndices::desde_toml(
+            "[[jugada]]\nmano = \"16\"\nbanca = \"1\"\naccion = \"pedir\"\nindice = 1"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,377 @@
+use crate::game::chart::{self, Casilla, CARTAS_BANCA};
+use crate::game::deck::{Carta, Palo};
+use crate::game::mistakes::Categoria;
+use crate::game::rules::ReglasMesa;
+use crate::game::strategy::{Accion, Situacion};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+const PALOS: [Palo; 4] = [
+    Palo::Corazones,
+    Palo::Diamantes,
+    Palo::Tréboles,
+    Palo::Picas,
+];
+
+// Respuestas y tiempo empleado en una situación
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Marca {
+    pub intentos: u32,
+    pub aciertos: u32,
+    pub milisegundos: u64,
+}
+
+impl Marca {
+    pub fn anotar(&mut self, acierto: bool, milisegundos: u64) {
+        self.intentos += 1;
+        if acierto {
+            self.aciertos += 1;
+        }
+        self.milisegundos += milisegundos;
+    }
+
+    pub fn precision(&self) -> f64 {
+        if self.intentos == 0 {
+            0.0
+        } else {
+            self.aciertos as f64 / self.intentos as f64
+        }
+    }
+
+    // Segundos de media por respuesta
+    pub fn tiempo_medio(&self) -> f64 {
+        if self.intentos == 0 {
+            0.0
+        } else {
+            self.milisegundos as f64 / self.intentos as f64 / 1000.0
+        }
+    }
+
+    // Peso en el repaso: las situaciones falladas o poco vistas salen más a menudo
+    pub fn peso(&self) -> f64 {
+        let fallos = self.intentos.saturating_sub(self.aciertos);
+        1.0 + 9.0 * (fallos as f64 + 1.0) / (self.intentos as f64 + 2.0)
+    }
+}
+
+// Marcas de cada situación entre sesiones, p. ej. ~/.local/share/ratjack/practica.toml
+//
+//     [situaciones."16 duro vs 10"]
+//     intentos = 4
+//     aciertos = 3
+//     milisegundos = 6100
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PuntosDebiles {
+    #[serde(default)]
+    pub situaciones: BTreeMap<String, Marca>,
+}
+
+impl PuntosDebiles {
+    pub fn desde_toml(texto: &str) -> Result<Self, String> {
+        let puntos: PuntosDebiles = toml::from_str(texto).map_err(|e| e.to_string())?;
+        // Un fichero editado a mano no puede tener más aciertos que intentos
+        if let Some((situacion, _)) = puntos
+            .situaciones
+            .iter()
+            .find(|(_, marca)| marca.aciertos > marca.intentos)
+        {
+            return Err(format!("{}: hay más aciertos que intentos", situacion));
+        }
+        Ok(puntos)
+    }
+
+    pub fn a_toml(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| e.to_string())
+    }
+
+    pub fn marca(&self, situacion: &Situacion) -> Marca {
+        self.situaciones
+            .get(&situacion.descripcion())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // Situaciones con algún fallo, de menos a más precisión
+    pub fn peores(&self, cuantas: usize) -> Vec<(&str, Marca)> {
+        let mut peores: Vec<(&str, Marca)> = self
+            .situaciones
+            .iter()
+            .filter(|(_, marca)| marca.aciertos < marca.intentos)
+            .map(|(situacion, marca)| (situacion.as_str(), *marca))
+            .collect();
+        peores.sort_by(|a, b| {
+            a.1.precision()
+                .total_cmp(&b.1.precision())
+                .then(b.1.intentos.cmp(&a.1.intentos))
+        });
+        peores.truncate(cuantas);
+        peores
+    }
+}
+
+// Mano del jugador y carta visible de la banca que hay que resolver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pregunta {
+    pub situacion: Situacion,
+    pub jugador: [Carta; 2],
+    pub banca: Carta,
+}
+
+// Todas las situaciones de la tabla de estrategia que se pueden repartir
+pub fn situaciones() -> Vec<Situacion> {
+    Categoria::TODAS
+        .iter()
+        .flat_map(|&categoria| {
+            chart::filas(categoria)
+                .into_iter()
+                // El 20 duro solo sale con una pareja de dieces
+                .filter(move |&fila| !(categoria == Categoria::Duras && fila == 20))
+                .flat_map(move |fila| {
+                    CARTAS_BANCA
+                        .iter()
+                        .map(move |&banca| chart::situacion(categoria, fila, banca))
+                })
+        })
+        .collect()
+}
+
+// Reparte una situación al azar; en el repaso pesan más los puntos débiles
+pub fn nueva_pregunta(rng: &mut impl Rng, repaso: Option<&PuntosDebiles>) -> Pregunta {
+    let todas = situaciones();
+    let situacion = match repaso {
+        Some(puntos) => *todas
+            .choose_weighted(rng, |situacion| puntos.marca(situacion).peso())
+            .expect("los pesos siempre son positivos"),
+        None => *todas.choose(rng).expect("hay situaciones"),
+    };
+    Pregunta {
+        situacion,
+        jugador: cartas_jugador(&situacion, rng),
+        banca: carta(situacion.banca, rng),
+    }
+}
+
+// Carta con los puntos dados; los dieces pueden ser figuras
+fn carta(puntos: u8, rng: &mut impl Rng) -> Carta {
+    let valor = match puntos {
+        11 => 1,
+        10 => rng.gen_range(10..=13),
+        puntos => puntos,
+    };
+    Carta {
+        valor,
+        palo: *PALOS.choose(rng).expect("hay palos"),
+    }
+}
+
+fn cartas_jugador(situacion: &Situacion, rng: &mut impl Rng) -> [Carta; 2] {
+    let total = situacion.total;
+    let (primera, segunda) = match (situacion.par, situacion.blanda) {
+        (Some(par), _) => (par, par),
+        (None, true) => (11, total - 11),
+        (None, false) => {
+            // Dos cartas distintas sin as que sumen el total
+            let primeras: Vec<u8> = (2..=10)
+                .filter(|&primera| {
+                    total
+                        .checked_sub(primera)
+                        .is_some_and(|segunda| (2..=10).contains(&segunda) && segunda != primera)
+                })
+                .collect();
+            let primera = *primeras.choose(rng).expect("total duro sin cartas");
+            (primera, total - primera)
+        }
+    };
+    [carta(primera, rng), carta(segunda, rng)]
+}
+
+// Respuesta a una pregunta comparada con la estrategia básica
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Respuesta {
+    pub situacion: Situacion,
+    pub tomada: Accion,
+    pub correcta: Casilla,
+    pub milisegundos: u64,
+}
+
+impl Respuesta {
+    pub fn acierto(&self) -> bool {
+        self.tomada == self.correcta.accion
+    }
+}
+
+// Corrige la jugada elegida; None si las reglas no la permiten con esta mano
+pub fn corregir(
+    situacion: &Situacion,
+    tomada: Accion,
+    reglas: &ReglasMesa,
+    milisegundos: u64,
+) -> Option<Respuesta> {
+    let opciones = chart::opciones(situacion, reglas);
+    let permitida = match tomada {
+        Accion::Pedir | Accion::Plantarse => true,
+        Accion::Doblar => opciones.doblar,
+        Accion::Dividir => opciones.dividir,
+        Accion::Rendirse => opciones.rendirse,
+    };
+    permitida.then(|| Respuesta {
+        situacion: *situacion,
+        tomada,
+        correcta: Casilla::nueva(situacion, reglas),
+        milisegundos,
+    })
+}
+
+// Sesión de práctica en curso
+pub struct Entrenamiento {
+    pub pregunta: Pregunta,
+    // Momento en que se repartió la pregunta, para medir el tiempo de respuesta
+    inicio: Instant,
+    pub ultima: Option<Respuesta>,
+    pub sesion: Marca,
+    // Si las preguntas se eligen según los puntos débiles
+    pub repaso: bool,
+    pub puntos: PuntosDebiles,
+}
+
+impl Entrenamiento {
+    pub fn nuevo(puntos: PuntosDebiles) -> Self {
+        let repaso = !puntos.situaciones.is_empty();
+        Entrenamiento {
+            pregunta: nueva_pregunta(&mut rand::thread_rng(), repaso.then_some(&puntos)),
+            inicio: Instant::now(),
+            ultima: None,
+            sesion: Marca::default(),
+            repaso,
+            puntos,
+        }
+    }
+
+    // Corrige la respuesta, la anota y reparte la siguiente pregunta
+    pub fn responder(&mut self, tomada: Accion, reglas: &ReglasMesa) -> Option<Respuesta> {
+        let milisegundos = self.inicio.elapsed().as_millis() as u64;
+        let respuesta = corregir(&self.pregunta.situacion, tomada, reglas, milisegundos)?;
+        self.sesion.anotar(respuesta.acierto(), milisegundos);
+        self.puntos
+            .situaciones
+            .entry(respuesta.situacion.descripcion())
+            .or_default()
+            .anotar(respuesta.acierto(), milisegundos);
+        self.ultima = Some(respuesta);
+
+        let repaso = self.repaso.then_some(&self.puntos);
+        self.pregunta = nueva_pregunta(&mut rand::thread_rng(), repaso);
+        self.inicio = Instant::now();
+        Some(respuesta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::hand::Mano;
+    use crate::game::rules::{PerfilMesa, Rendicion};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_preguntas_coinciden_con_su_situacion() {
+        assert_eq!(situaciones().len(), (15 + 8 + 10) * 10);
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..500 {
+            let pregunta = nueva_pregunta(&mut rng, None);
+            let mut mano = Mano::nueva(0);
+            mano.cartas = pregunta.jugador.to_vec();
+            assert_eq!(Situacion::nueva(&mano, &pregunta.banca), pregunta.situacion);
+        }
+    }
+
+    #[test]
+    fn test_repaso_prefiere_los_fallos() {
+        let mut puntos = PuntosDebiles::default();
+        let debil = chart::situacion(Categoria::Blandas, 18, 9);
+        let marca = puntos.situaciones.entry(debil.descripcion()).or_default();
+        for _ in 0..10 {
+            marca.anotar(false, 3000);
+        }
+        for situacion in situaciones().iter().filter(|&&s| s != debil) {
+            let marca = puntos
+                .situaciones
+                .entry(situacion.descripcion())
+                .or_default();
+            for _ in 0..10 {
+                marca.anotar(true, 1000);
+            }
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let veces = (0..1000)
+            .filter(|_| nueva_pregunta(&mut rng, Some(&puntos)).situacion == debil)
+            .count();
+        // Sin repaso saldría unas 3 veces de cada 1000; con repaso, unas 16
+        assert!(veces > 10, "{}", veces);
+        assert_eq!(
+            puntos.peores(5),
+            vec![("18 blando vs 9", puntos.marca(&debil))]
+        );
+        assert_eq!(puntos.marca(&debil).tiempo_medio(), 3.0);
+    }
+
+    #[test]
+    fn test_corregir() {
+        let reglas = PerfilMesa::VegasStrip.reglas();
+        let dieciseis = chart::situacion(Categoria::Duras, 16, 10);
+        let respuesta = corregir(&dieciseis, Accion::Pedir, &reglas, 800).unwrap();
+        assert!(!respuesta.acierto());
+        assert_eq!(respuesta.correcta.accion, Accion::Rendirse);
+        assert!(corregir(&dieciseis, Accion::Dividir, &reglas, 0).is_none());
+
+        let sin_rendicion = ReglasMesa {
+            rendicion: Rendicion::No,
+            ..reglas
+        };
+        assert!(corregir(&dieciseis, Accion::Rendirse, &sin_rendicion, 0).is_none());
+        assert!(corregir(&dieciseis, Accion::Pedir, &sin_rendicion, 0)
+            .unwrap()
+            .acierto());
+    }
+
+    #[test]
+    fn test_puntos_debiles_toml() {
+        let mut puntos = PuntosDebiles::default();
+        let situacion = chart::situacion(Categoria::Parejas, 8, 11);
+        let mut entrenamiento = Entrenamiento::nuevo(puntos.clone());
+        entrenamiento.pregunta.situacion = situacion;
+        entrenamiento
+            .responder(Accion::Dividir, &PerfilMesa::VegasStrip.reglas())
+            .unwrap();
+        assert_eq!(entrenamiento.sesion.aciertos, 1);
+        puntos = entrenamiento.puntos;
+        assert_eq!(puntos.marca(&situacion).intentos, 1);
+
+        let texto = puntos.a_toml().unwrap();
+        assert_eq!(PuntosDebiles::desde_toml(&texto).unwrap(), puntos);
+        assert_eq!(
+            PuntosDebiles::desde_toml("").unwrap(),
+            PuntosDebiles::default()
+        );
+        assert!(PuntosDebiles::desde_toml("fallos = 1").is_err());
+        assert!(PuntosDebiles::desde_toml(
+            "[situaciones.\"16 duro vs 10\"]\nintentos = 1\naciertos = 2"
+        )
+        .is_err());
+
+        // Aunque la marca venga de otro sitio, el peso nunca se desborda
+        let imposible = Marca {
+            intentos: 1,
+            aciertos: 2,
+            milisegundos: 0,
+        };
+        assert!(imposible.peso() < Marca::default().peso());
+    }
+}
@@ -0,0 +1,629 @@
+use crate::game::deck::Carta;
+use crate::game::rules::{ReglaBanca, ReglaDoblar, ReglaPeek, ReglasMesa, Rendicion};
+use crate::game::strategy::{Accion, Opciones, Situacion};
+use std::collections::HashMap;
+
+// Valores de carta posibles; el as cuenta como 11
+const CARTAS: [u8; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+// Probabilidad de cada valor con un shoe infinito
+fn probabilidad(carta: u8) -> f64 {
+    if carta == 10 {
+        4.0 / 13.0
+    } else {
+        1.0 / 13.0
+    }
+}
+
+// Suma una carta a un total blando o duro
+fn sumar(total: u8, blanda: bool, carta: u8) -> (u8, bool) {
+    let (mut total, mut blanda) = match carta {
+        11 if total + 11 <= 21 => (total + 11, true),
+        11 => (total + 1, blanda),
+        _ => (total + carta, blanda),
+    };
+    if total > 21 && blanda {
+        total -= 10;
+        blanda = false;
+    }
+    (total, blanda)
+}
+
+// Valor esperado de cada jugada frente a una carta visible de la banca, en apuestas iniciales
+pub struct Evaluador<'a> {
+    reglas: &'a ReglasMesa,
+    // Totales finales de la banca sin blackjack: 17..=21 y pasarse
+    finales: [f64; 6],
+    // Probabilidad de blackjack de la banca que el jugador aún no conoce
+    blackjack: f64,
+    memo_pedir: HashMap<(u8, bool), f64>,
+}
+
+impl<'a> Evaluador<'a> {
+    pub fn nuevo(carta_banca: u8, reglas: &'a ReglasMesa) -> Self {
+        let mut finales = [0.0; 6];
+        let mut blackjack = 0.0;
+        let (inicial, blanda) = sumar(0, false, carta_banca);
+        for carta in CARTAS {
+            let (total, blanda) = sumar(inicial, blanda, carta);
+            if total == 21 {
+                blackjack += probabilidad(carta);
+            } else {
+                robar_banca(total, blanda, probabilidad(carta), reglas, &mut finales);
+            }
+        }
+
+        let suma: f64 = finales.iter().sum();
+        finales.iter_mut().for_each(|p| *p /= suma);
+
+        // Con carta oculta americana el jugador ya sabe que la banca no tiene blackjack
+        if reglas.peek == ReglaPeek::Americana {
+            blackjack = 0.0;
+        }
+
+        Evaluador {
+            reglas,
+            finales,
+            blackjack,
+            memo_pedir: HashMap::new(),
+        }
+    }
+
+    pub fn ev(&mut self, situacion: &Situacion, accion: Accion) -> f64 {
+        let (total, blanda) = (situacion.total, situacion.blanda);
+        let ev = match accion {
+            Accion::Plantarse => self.plantarse(total),
+            Accion::Pedir => self.pedir(total, blanda),
+            Accion::Doblar => self.doblar(total, blanda),
+            Accion::Dividir => match situacion.par {
+                Some(carta) => 2.0 * self.mano_dividida(carta),
+                None => self.plantarse(total),
+            },
+            Accion::Rendirse => -0.5,
+        };
+        ajustar_blackjack(ev, accion, self.blackjack, self.reglas)
+    }
+
+    fn plantarse(&self, total: u8) -> f64 {
+        valor_plantarse(total, &self.finales)
+    }
+
+    // Pide carta y sigue jugando de la mejor forma posible
+    fn pedir(&mut self, total: u8, blanda: bool) -> f64 {
+        if let Some(ev) = self.memo_pedir.get(&(total, blanda)) {
+            return *ev;
+        }
+        let ev = CARTAS
+            .iter()
+            .map(|&carta| {
+                let (total, blanda) = sumar(total, blanda, carta);
+                let ev = if total > 21 {
+                    -1.0
+                } else {
+                    self.plantarse(total).max(self.pedir(total, blanda))
+                };
+                probabilidad(carta) * ev
+            })
+            .sum();
+        self.memo_pedir.insert((total, blanda), ev);
+        ev
+    }
+
+    fn doblar(&self, total: u8, blanda: bool) -> f64 {
+        2.0 * CARTAS
+            .iter()
+            .map(|&carta| {
+                let (total, _) = sumar(total, blanda, carta);
+                probabilidad(carta) * self.plantarse(total)
+            })
+            .sum::<f64>()
+    }
+
+    // Una de las manos tras dividir, sin volver a dividir. Es una aproximación: no quita
+    // las cartas que recibe la otra mano ni contempla redividir, así que el EV de dividir
+    // de la mesa puede diferir algo del real
+    fn mano_dividida(&mut self, carta_par: u8) -> f64 {
+        let (inicial, blanda) = sumar(0, false, carta_par);
+        CARTAS
+            .iter()
+            .map(|&carta| {
+                let (total, blanda) = sumar(inicial, blanda, carta);
+                // Los ases divididos solo reciben una carta
+                let ev = if carta_par == 11 {
+                    self.plantarse(total)
+                } else {
+                    let mut ev = self.plantarse(total).max(self.pedir(total, blanda));
+                    if self.reglas.doblar_tras_dividir && permite_doblar(total, self.reglas) {
+                        ev = ev.max(self.doblar(total, blanda));
+                    }
+                    ev
+                };
+                probabilidad(carta) * ev
+            })
+            .sum()
+    }
+}
+
+// Cartas que quedan por salir, por valor (el as cuenta como 11)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Composicion {
+    cartas: [u16; 10],
+    total: u16,
+}
+
+impl Composicion {
+    pub fn completa(mazos: u8) -> Self {
+        let mut cartas = [4 * mazos as u16; 10];
+        cartas[8] = 16 * mazos as u16;
+        Composicion {
+            cartas,
+            total: 52 * mazos as u16,
+        }
+    }
+
+    pub fn quitar(&mut self, carta: &Carta) {
+        let indice = (carta.puntos() - 2) as usize;
+        if self.cartas[indice] > 0 {
+            self.cartas[indice] -= 1;
+            self.total -= 1;
+        }
+    }
+
+    pub fn devolver(&mut self, carta: &Carta) {
+        self.cartas[(carta.puntos() - 2) as usize] += 1;
+        self.total += 1;
+    }
+
+    fn probabilidad(&self, carta: u8) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.cartas[(carta - 2) as usize] as f64 / self.total as f64
+        }
+    }
+
+    fn sin(&self, carta: u8) -> Self {
+        let mut resto = *self;
+        resto.cartas[(carta - 2) as usize] -= 1;
+        resto.total -= 1;
+        resto
+    }
+
+    // Valores que aún pueden salir con su probabilidad
+    fn siguientes(&self) -> impl Iterator<Item = (u8, f64, Composicion)> + '_ {
+        CARTAS
+            .into_iter()
+            .filter(|&carta| self.cartas[(carta - 2) as usize] > 0)
+            .map(|carta| (carta, self.probabilidad(carta), self.sin(carta)))
+    }
+}
+
+// Valor esperado exacto según las cartas que quedan en el shoe. Cada carta que
+// sale cambia la probabilidad de las siguientes, tanto para el jugador como para la banca
+pub struct EvaluadorExacto<'a> {
+    reglas: &'a ReglasMesa,
+    carta_banca: u8,
+    composicion: Composicion,
+    // Totales finales de la banca y probabilidad de blackjack para cada composición
+    memo_banca: HashMap<Composicion, ([f64; 6], f64)>,
+    // Totales finales de la banca desde una mano a medias. La composición ya refleja las
+    // cartas robadas, así que el orden en que salieron no importa
+    memo_robar: HashMap<(Composicion, u8, bool), [f64; 6]>,
+    memo_pedir: HashMap<(Composicion, u8, bool), f64>,
+}
+
+impl<'a> EvaluadorExacto<'a> {
+    // La composición no debe incluir las cartas ya vistas en la mesa
+    pub fn nuevo(composicion: Composicion, carta_banca: u8, reglas: &'a ReglasMesa) -> Self {
+        EvaluadorExacto {
+            reglas,
+            carta_banca,
+            composicion,
+            memo_banca: HashMap::new(),
+            memo_robar: HashMap::new(),
+            memo_pedir: HashMap::new(),
+        }
+    }
+
+    pub fn ev(&mut self, situacion: &Situacion, accion: Accion) -> f64 {
+        let composicion = self.composicion;
+        let (total, blanda) = (situacion.total, situacion.blanda);
+        let ev = match accion {
+            Accion::Plantarse => self.plantarse(composicion, total),
+            Accion::Pedir => self.pedir(composicion, total, blanda),
+            Accion::Doblar => self.doblar(composicion, total, blanda),
+            // Aproximado: dos manos independientes con la misma composición
+            Accion::Dividir => match situacion.par {
+                Some(carta) => 2.0 * self.mano_dividida(composicion, carta),
+                None => self.plantarse(composicion, total),
+            },
+            Accion::Rendirse => -0.5,
+        };
+        let blackjack = self.banca(composicion).1;
+        ajustar_blackjack(ev, accion, blackjack, self.reglas)
+    }
+
+    // Valor esperado de cada jugada permitida en la decisión
+    pub fn jugadas(&mut self, situacion: &Situacion, opciones: Opciones) -> Vec<(Accion, f64)> {
+        [
+            (Accion::Pedir, true),
+            (Accion::Plantarse, true),
+            (Accion::Doblar, opciones.doblar),
+            (Accion::Dividir, opciones.dividir),
+            (Accion::Rendirse, opciones.rendirse),
+        ]
+        .into_iter()
+        .filter(|(_, permitida)| *permitida)
+        .map(|(accion, _)| (accion, self.ev(situacion, accion)))
+        .collect()
+    }
+
+    // Resultado de la banca robando de la composición dada
+    // Probabilidad de cada final de la banca: 17..=21, pasarse y blackjack. No tiene en
+    // cuenta lo que el jugador sabe tras el peek
+    pub fn resultados_banca(&mut self) -> [f64; 7] {
+        self.repartir_banca(self.composicion)
+    }
+
+    fn repartir_banca(&mut self, composicion: Composicion) -> [f64; 7] {
+        let mut resultados = [0.0; 7];
+        let (inicial, blanda) = sumar(0, false, self.carta_banca);
+        for (carta, p, resto) in composicion.siguientes() {
+            let (total, blanda) = sumar(inicial, blanda, carta);
+            if total == 21 {
+                resultados[6] += p;
+            } else {
+                let robadas = self.robar_banca(resto, total, blanda);
+                resultados
+                    .iter_mut()
+                    .zip(robadas)
+                    .for_each(|(r, q)| *r += p * q);
+            }
+        }
+        resultados
+    }
+
+    // Totales finales de la banca sin blackjack y probabilidad de blackjack que el jugador aún no conoce
+    fn banca(&mut self, composicion: Composicion) -> ([f64; 6], f64) {
+        if let Some(resultado) = self.memo_banca.get(&composicion) {
+            return *resultado;
+        }
+        let resultados = self.repartir_banca(composicion);
+        let mut finales = [0.0; 6];
+        finales.copy_from_slice(&resultados[..6]);
+        let suma: f64 = finales.iter().sum();
+        if suma > 0.0 {
+            finales.iter_mut().for_each(|p| *p /= suma);
+        }
+
+        // Con carta oculta americana el jugador ya sabe que la banca no tiene blackjack
+        let blackjack = if self.reglas.peek == ReglaPeek::Americana {
+            0.0
+        } else {
+            resultados[6]
+        };
+        self.memo_banca.insert(composicion, (finales, blackjack));
+        (finales, blackjack)
+    }
+
+    fn robar_banca(&mut self, composicion: Composicion, total: u8, blanda: bool) -> [f64; 6] {
+        let mut finales = [0.0; 6];
+        let pide = total < 17 || (total == 17 && blanda && self.reglas.banca == ReglaBanca::H17);
+        if !pide {
+            finales[(total.min(22) - 17) as usize] = 1.0;
+            return finales;
+        }
+        if let Some(finales) = self.memo_robar.get(&(composicion, total, blanda)) {
+            return *finales;
+        }
+        for (carta, p, resto) in composicion.siguientes() {
+            let (total, blanda) = sumar(total, blanda, carta);
+            let robadas = self.robar_banca(resto, total, blanda);
+            finales
+                .iter_mut()
+                .zip(robadas)
+                .for_each(|(f, r)| *f += p * r);
+        }
+        self.memo_robar
+            .insert((composicion, total, blanda), finales);
+        finales
+    }
+
+    fn plantarse(&mut self, composicion: Composicion, total: u8) -> f64 {
+        if total > 21 {
+            return -1.0;
+        }
+        valor_plantarse(total, &self.banca(composicion).0)
+    }
+
+    fn pedir(&mut self, composicion: Composicion, total: u8, blanda: bool) -> f64 {
+        if let Some(ev) = self.memo_pedir.get(&(composicion, total, blanda)) {
+            return *ev;
+        }
+        let ev = composicion
+            .siguientes()
+            .map(|(carta, p, resto)| {
+                let (total, blanda) = sumar(total, blanda, carta);
+                let ev = if total > 21 {
+                    -1.0
+                } else {
+                    let plantado = self.plantarse(resto, total);
+                    plantado.max(self.pedir(resto, total, blanda))
+                };
+                p * ev
+            })
+            .sum();
+        self.memo_pedir.insert((composicion, total, blanda), ev);
+        ev
+    }
+
+    fn doblar(&mut self, composicion: Composicion, total: u8, blanda: bool) -> f64 {
+        2.0 * composicion
+            .siguientes()
+            .map(|(carta, p, resto)| {
+                let (total, _) = sumar(total, blanda, carta);
+                p * self.plantarse(resto, total)
+            })
+            .sum::<f64>()
+    }
+
+    // Una de las manos tras dividir, sin volver a dividir. Es una aproximación: no quita
+    // las cartas que recibe la otra mano ni contempla redividir, así que el EV de dividir
+    // de la mesa puede diferir algo del real
+    fn mano_dividida(&mut self, composicion: Composicion, carta_par: u8) -> f64 {
+        let (inicial, blanda) = sumar(0, false, carta_par);
+        composicion
+            .siguientes()
+            .map(|(carta, p, resto)| {
+                let (total, blanda) = sumar(inicial, blanda, carta);
+                // Los ases divididos solo reciben una carta
+                let ev = if carta_par == 11 {
+                    self.plantarse(resto, total)
+                } else {
+                    let mut ev = self
+                        .plantarse(resto, total)
+                        .max(self.pedir(resto, total, blanda));
+                    if self.reglas.doblar_tras_dividir && permite_doblar(total, self.reglas) {
+                        ev = ev.max(self.doblar(resto, total, blanda));
+                    }
+                    ev
+                };
+                p * ev
+            })
+            .sum()
+    }
+}
+
+// Valor de plantarse con un total frente a los totales finales de la banca
+fn valor_plantarse(total: u8, finales: &[f64; 6]) -> f64 {
+    if total > 21 {
+        return -1.0;
+    }
+    let pasada = finales[5];
+    finales[..5]
+        .iter()
+        .zip(17..=21)
+        .map(|(p, banca)| match total.cmp(&banca) {
+            std::cmp::Ordering::Greater => *p,
+            std::cmp::Ordering::Equal => 0.0,
+            std::cmp::Ordering::Less => -*p,
+        })
+        .sum::<f64>()
+        + pasada
+}
+
+// Tiene en cuenta el blackjack de la banca que el jugador aún no conoce
+fn ajustar_blackjack(ev: f64, accion: Accion, blackjack: f64, reglas: &ReglasMesa) -> f64 {
+    // Lo que se pierde ante un blackjack de la banca sin carta oculta
+    let toda_la_apuesta = if reglas.peek == ReglaPeek::Europea {
+        2.0
+    } else {
+        1.0
+    };
+    let perdida = match accion {
+        Accion::Doblar | Accion::Dividir => toda_la_apuesta,
+        // La rendición temprana se hace antes de saber si hay blackjack
+        Accion::Rendirse if reglas.rendicion == Rendicion::Temprana => return ev,
+        _ => 1.0,
+    };
+    (1.0 - blackjack) * ev - blackjack * perdida
+}
+
+// Totales que las reglas de la mesa dejan doblar
+pub fn permite_doblar(total: u8, reglas: &ReglasMesa) -> bool {
+    match reglas.doblar {
+        ReglaDoblar::Cualquiera => true,
+        ReglaDoblar::NueveAOnce => (9..=11).contains(&total),
+        ReglaDoblar::DiezAOnce => (10..=11).contains(&total),
+    }
+}
+
+fn robar_banca(total: u8, blanda: bool, p: f64, reglas: &ReglasMesa, finales: &mut [f64; 6]) {
+    let pide = total < 17 || (total == 17 && blanda && reglas.banca == ReglaBanca::H17);
+    if pide {
+        for carta in CARTAS {
+            let (total, blanda) = sumar(total, blanda, carta);
+            robar_banca(total, blanda, p * probabilidad(carta), reglas, finales);
+        }
+    } else if total > 21 {
+        finales[5] += p;
+    } else {
+        finales[(total - 17) as usize] += p;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dura(total: u8, banca: u8) -> Situacion {
+        Situacion {
+            total,
+            blanda: false,
+            par: None,
+            banca,
+        }
+    }
+
+    #[test]
+    fn test_sumar() {
+        assert_eq!(sumar(0, false, 11), (11, true));
+        assert_eq!(sumar(11, true, 11), (12, true));
+        assert_eq!(sumar(17, true, 10), (17, false));
+        assert_eq!(sumar(20, false, 11), (21, false));
+    }
+
+    #[test]
+    fn test_banca_se_pasa() {
+        let reglas = ReglasMesa::default();
+        // Con un 6 visible la banca se pasa cerca del 42% de las veces
+        let seis = Evaluador::nuevo(6, &reglas);
+        assert!((seis.finales[5] - 0.42).abs() < 0.01);
+        assert!((seis.finales.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(seis.blackjack, 0.0);
+    }
+
+    #[test]
+    fn test_ev_jugadas() {
+        let reglas = ReglasMesa::default();
+        let mut evaluador = Evaluador::nuevo(6, &reglas);
+        let once = dura(11, 6);
+        assert!(evaluador.ev(&once, Accion::Doblar) > evaluador.ev(&once, Accion::Pedir));
+
+        let mut evaluador = Evaluador::nuevo(10, &reglas);
+        let dieciseis = dura(16, 10);
+        assert!(
+            evaluador.ev(&dieciseis, Accion::Rendirse)
+                > evaluador.ev(&dieciseis, Accion::Plantarse)
+        );
+        assert_eq!(evaluador.ev(&dieciseis, Accion::Rendirse), -0.5);
+
+        let ases = Situacion {
+            total: 12,
+            blanda: true,
+            par: Some(11),
+            banca: 10,
+        };
+        assert!(evaluador.ev(&ases, Accion::Dividir) > evaluador.ev(&ases, Accion::Pedir));
+    }
+
+    #[test]
+    fn test_ev_sin_carta_oculta() {
+        let reglas = ReglasMesa {
+            peek: ReglaPeek::Europea,
+            ..ReglasMesa::default()
+        };
+        let mut evaluador = Evaluador::nuevo(11, &reglas);
+        assert!(evaluador.blackjack > 0.3);
+        // Sin carta oculta, doblar 11 contra un as sale peor que pedir
+        let once = dura(11, 11);
+        assert!(evaluador.ev(&once, Accion::Doblar) < evaluador.ev(&once, Accion::Pedir));
+    }
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: crate::game::deck::Palo::Picas,
+        }
+    }
+
+    #[test]
+    fn test_composicion() {
+        let mut composicion = Composicion::completa(1);
+        assert_eq!(composicion.total, 52);
+        assert!((composicion.probabilidad(10) - 16.0 / 52.0).abs() < 1e-12);
+
+        composicion.quitar(&carta(13));
+        composicion.quitar(&carta(1));
+        assert_eq!(composicion.total, 50);
+        assert!((composicion.probabilidad(10) - 15.0 / 50.0).abs() < 1e-12);
+        assert!((composicion.probabilidad(11) - 3.0 / 50.0).abs() < 1e-12);
+
+        composicion.devolver(&carta(1));
+        assert_eq!(composicion.total, 51);
+    }
+
+    #[test]
+    fn test_ev_exacto_con_muchos_mazos() {
+        // Con un shoe grande el resultado se acerca al de un shoe infinito
+        let reglas = ReglasMesa::default();
+        let mut composicion = Composicion::completa(8);
+        for valor in [10, 6, 10] {
+            composicion.quitar(&carta(valor));
+        }
+        let mut exacto = EvaluadorExacto::nuevo(composicion, 10, &reglas);
+        let mut infinito = Evaluador::nuevo(10, &reglas);
+        let dieciseis = dura(16, 10);
+        for accion in [Accion::Plantarse, Accion::Pedir, Accion::Doblar] {
+            let diferencia = exacto.ev(&dieciseis, accion) - infinito.ev(&dieciseis, accion);
+            assert!(diferencia.abs() < 0.01, "{:?}: {}", accion, diferencia);
+        }
+        assert_eq!(exacto.ev(&dieciseis, Accion::Rendirse), -0.5);
+    }
+
+    #[test]
+    fn test_ev_exacto_segun_composicion() {
+        let reglas = ReglasMesa::default();
+        let once = dura(11, 6);
+        let ev_doblar = |quitar: u8| {
+            let mut composicion = Composicion::completa(1);
+            for valor in [5, 6, 6] {
+                composicion.quitar(&carta(valor));
+            }
+            // Se retiran ocho cartas del valor indicado
+            for _ in 0..8 {
+                composicion.quitar(&carta(quitar));
+            }
+            EvaluadorExacto::nuevo(composicion, 6, &reglas).ev(&once, Accion::Doblar)
+        };
+        // Doblar 11 vale más cuando quedan muchas cartas de diez
+        assert!(ev_doblar(2) > ev_doblar(10) + 0.1);
+
+        let mut composicion = Composicion::completa(1);
+        for valor in [8, 8, 10] {
+            composicion.quitar(&carta(valor));
+        }
+        let ochos = Situacion {
+            total: 16,
+            blanda: false,
+            par: Some(8),
+            banca: 10,
+        };
+        let opciones = Opciones {
+            doblar: true,
+            dividir: true,
+            rendirse: true,
+        };
+        let jugadas = EvaluadorExacto::nuevo(composicion, 10, &reglas).jugadas(&ochos, opciones);
+        assert_eq!(jugadas.len(), 5);
+        let mejor = jugadas
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(accion, _)| *accion);
+        assert_eq!(mejor, Some(Accion::Dividir));
+    }
+
+    #[test]
+    fn test_resultados_banca() {
+        let reglas = ReglasMesa::default();
+        let mut composicion = Composicion::completa(6);
+        composicion.quitar(&carta(1));
+        let resultados = EvaluadorExacto::nuevo(composicion, 11, &reglas).resultados_banca();
+        assert!((resultados.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        // Con un as visible, el blackjack sale con la probabilidad de un diez
+        assert!((resultados[6] - 96.0 / 311.0).abs() < 1e-12);
+
+        // Con H17 la banca se pasa más a menudo mostrando un 6
+        let pasarse = |banca| {
+            let reglas = ReglasMesa {
+                banca,
+                ..ReglasMesa::default()
+            };
+            let mut composicion = Composicion::completa(6);
+            composicion.quitar(&carta(6));
+            EvaluadorExacto::nuevo(composicion, 6, &reglas).resultados_banca()[5]
+        };
+        assert!(pasarse(ReglaBanca::H17) > pasarse(ReglaBanca::S17));
+        assert!((pasarse(ReglaBanca::S17) - 0.42).abs() < 0.01);
+    }
+}
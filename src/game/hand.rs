@@ -0,0 +1,198 @@
+use crate::game::deck::Carta;
+use crate::game::shoe::Shoe;
+use serde::{Deserialize, Serialize};
+
+// Valor de una mano: blanda si tiene un as que aún cuenta como 11
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Valor {
+    pub total: u8,
+    pub blanda: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mano {
+    pub cartas: Vec<Carta>,
+    pub puntos: u8,
+    pub apuesta: u32,
+    pub doblada: bool,
+    pub dividida: bool,
+    pub rendida: bool,
+    pub terminada: bool,
+}
+
+impl Mano {
+    pub fn nueva(apuesta: u32) -> Self {
+        Mano {
+            cartas: Vec::new(),
+            puntos: 0,
+            apuesta,
+            doblada: false,
+            dividida: false,
+            rendida: false,
+            terminada: false,
+        }
+    }
+
+    pub fn tomar_carta(&mut self, shoe: &mut Shoe) {
+        if let Some(carta) = shoe.robar() {
+            self.cartas.push(carta);
+        }
+        self.puntos = self.puntaje();
+    }
+
+    pub fn puntaje(&self) -> u8 {
+        self.valor().total
+    }
+
+    pub fn valor(&self) -> Valor {
+        let mut total = 0;
+        let mut ases = 0;
+
+        for carta in &self.cartas {
+            total += carta.puntos();
+            if carta.valor == 1 {
+                ases += 1;
+            }
+        }
+
+        while total > 21 && ases > 0 {
+            total -= 10;
+            ases -= 1;
+        }
+
+        Valor {
+            total,
+            blanda: ases > 0,
+        }
+    }
+
+    // Dos cartas del mismo valor (las figuras y el 10 cuentan como iguales)
+    pub fn es_par(&self) -> bool {
+        self.cartas.len() == 2 && self.cartas[0].puntos() == self.cartas[1].puntos()
+    }
+
+    // Mano que proviene de dividir una pareja de ases
+    pub fn es_as_dividido(&self) -> bool {
+        self.dividida && self.cartas.first().is_some_and(|c| c.valor == 1)
+    }
+
+    // Blackjack natural: 21 con las dos primeras cartas de una mano sin dividir
+    pub fn es_blackjack(&self) -> bool {
+        self.cartas.len() == 2 && !self.dividida && self.puntaje() == 21
+    }
+
+    pub fn pasada(&self) -> bool {
+        self.puntos > 21
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::deck::Palo;
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: Palo::Corazones,
+        }
+    }
+
+    #[test]
+    fn test_mano_nueva() {
+        let mano = Mano::nueva(5);
+        assert!(mano.cartas.is_empty());
+        assert_eq!(mano.apuesta, 5);
+        assert_eq!(mano.puntos, 0);
+        assert!(!mano.doblada && !mano.dividida && !mano.terminada);
+    }
+
+    #[test]
+    fn test_tomar_carta_actualiza_puntos() {
+        let mut mano = Mano::nueva(1);
+        let mut shoe = Shoe::from(vec![carta(1), carta(9)]);
+
+        mano.tomar_carta(&mut shoe);
+        assert_eq!(mano.puntos, 9);
+        mano.tomar_carta(&mut shoe);
+        assert_eq!(mano.puntos, 20);
+        assert_eq!(shoe.restantes(), 0);
+    }
+
+    #[test]
+    fn test_es_par() {
+        let mut mano = Mano::nueva(1);
+        mano.cartas = vec![carta(8), carta(8)];
+        assert!(mano.es_par());
+
+        mano.cartas = vec![carta(10), carta(13)];
+        assert!(mano.es_par());
+
+        mano.cartas = vec![carta(8), carta(9)];
+        assert!(!mano.es_par());
+
+        mano.cartas = vec![carta(8), carta(8), carta(8)];
+        assert!(!mano.es_par());
+    }
+
+    #[test]
+    fn test_es_as_dividido() {
+        let mut mano = Mano::nueva(1);
+        mano.cartas = vec![carta(1), carta(5)];
+        assert!(!mano.es_as_dividido());
+
+        mano.dividida = true;
+        assert!(mano.es_as_dividido());
+
+        mano.cartas = vec![carta(5), carta(1)];
+        assert!(!mano.es_as_dividido());
+    }
+
+    #[test]
+    fn test_es_blackjack() {
+        let mut mano = Mano::nueva(1);
+        mano.cartas = vec![carta(1), carta(12)];
+        assert!(mano.es_blackjack());
+
+        mano.dividida = true;
+        assert!(!mano.es_blackjack());
+
+        mano.dividida = false;
+        mano.cartas = vec![carta(7), carta(4), carta(10)];
+        assert!(!mano.es_blackjack());
+    }
+
+    #[test]
+    fn test_valor_blando_y_duro() {
+        let mut mano = Mano::nueva(1);
+        mano.cartas = vec![carta(1), carta(6)];
+        assert_eq!(
+            mano.valor(),
+            Valor {
+                total: 17,
+                blanda: true
+            }
+        );
+
+        mano.cartas = vec![carta(1), carta(6), carta(10)];
+        assert_eq!(
+            mano.valor(),
+            Valor {
+                total: 17,
+                blanda: false
+            }
+        );
+
+        mano.cartas = vec![carta(1), carta(1), carta(5)];
+        assert_eq!(
+            mano.valor(),
+            Valor {
+                total: 17,
+                blanda: true
+            }
+        );
+
+        mano.cartas = vec![carta(10), carta(7)];
+        assert!(!mano.valor().blanda);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::game::deck::Carta;
+use crate::game::logic::{Liquidacion, Resultado};
+use crate::game::player::Jugador;
+use crate::game::rules::ReglasMesa;
+use crate::game::strategy::Accion;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Una jugada del jugador y las cartas que salieron del shoe con ella
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Paso {
+    pub mano: usize,
+    pub accion: Accion,
+    pub cartas: Vec<Carta>,
+}
+
+// Mano del jugador tal como acabó la ronda
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManoJugada {
+    pub cartas: Vec<Carta>,
+    pub apuesta: u32,
+    #[serde(default)]
+    pub doblada: bool,
+    pub resultado: Resultado,
+}
+
+// Registro completo de una ronda para el historial de manos
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RondaJugada {
+    pub ronda: u32,
+    // Segundos desde 1970 al empezar la ronda
+    pub fecha: u64,
+    pub semilla: u64,
+    // Reglas con las que se jugó, para poder repetir la ronda
+    #[serde(default)]
+    pub reglas: ReglasMesa,
+    // Cartas que ya habían salido del shoe antes del reparto
+    pub posicion: usize,
+    pub apuesta: u32,
+    pub seguro: u32,
+    // Reparto inicial
+    pub jugador: Vec<Carta>,
+    pub banca: Vec<Carta>,
+    pub acciones: Vec<Paso>,
+    // Cartas que roba la banca después del reparto
+    pub robadas_banca: Vec<Carta>,
+    pub manos: Vec<ManoJugada>,
+    pub saldo_inicial: u32,
+    pub saldo_final: u32,
+    pub neto: i64,
+}
+
+impl RondaJugada {
+    // Empieza el registro justo después del reparto. `saldo` es el de antes de apostar
+    pub fn empezar(
+        ronda: u32,
+        semilla: u64,
+        reglas: &ReglasMesa,
+        posicion: usize,
+        saldo: u32,
+        jugador: &Jugador,
+        banca: &Jugador,
+    ) -> Self {
+        let fecha = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duracion| duracion.as_secs());
+        RondaJugada {
+            ronda,
+            fecha,
+            semilla,
+            reglas: reglas.clone(),
+            posicion,
+            apuesta: jugador.mano().apuesta,
+            seguro: 0,
+            jugador: jugador.mano().cartas.clone(),
+            banca: banca.mano().cartas.clone(),
+            acciones: Vec::new(),
+            robadas_banca: Vec::new(),
+            manos: Vec::new(),
+            saldo_inicial: saldo,
+            saldo_final: saldo,
+            neto: 0,
+        }
+    }
+
+    pub fn anotar(&mut self, mano: usize, accion: Accion, cartas: &[Carta]) {
+        self.acciones.push(Paso {
+            mano,
+            accion,
+            cartas: cartas.to_vec(),
+        });
+    }
+
+    // Completa el registro con la mano final de la banca y lo cobrado
+    pub fn terminar(&mut self, jugador: &Jugador, banca: &Jugador, liquidacion: &Liquidacion) {
+        self.robadas_banca = banca
+            .mano()
+            .cartas
+            .get(self.banca.len()..)
+            .unwrap_or_default()
+            .to_vec();
+        self.manos = jugador
+            .manos
+            .iter()
+            .zip(&liquidacion.resultados)
+            .map(|(mano, &resultado)| ManoJugada {
+                cartas: mano.cartas.clone(),
+                apuesta: mano.apuesta,
+                doblada: mano.doblada,
+                resultado,
+            })
+            .collect();
+        self.saldo_final = jugador.saldo;
+        self.neto = jugador.saldo as i64 - self.saldo_inicial as i64;
+    }
+}
+
+// Añade una ronda como una línea de JSON
+pub fn escribir_jsonl(destino: &mut impl Write, ronda: &RondaJugada) -> Result<(), String> {
+    let linea = serde_json::to_string(ronda).map_err(|e| e.to_string())?;
+    writeln!(destino, "{}", linea).map_err(|e| e.to_string())
+}
+
+// Lee un historial en JSON Lines, una ronda por línea. Las líneas que no se entienden,
+// como la última a medio escribir si el juego se cerró de golpe, se saltan y se cuentan
+pub fn leer_jsonl(origen: impl BufRead) -> Result<(Vec<RondaJugada>, usize), String> {
+    let mut rondas = Vec::new();
+    let mut descartadas = 0;
+    for linea in origen.lines() {
+        let linea = linea.map_err(|e| e.to_string())?;
+        if linea.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&linea) {
+            Ok(ronda) => rondas.push(ronda),
+            Err(_) => descartadas += 1,
+        }
+    }
+    Ok((rondas, descartadas))
+}
+
+// Fila plana para hojas de cálculo: las listas van como texto
+#[derive(Serialize)]
+struct FilaCsv {
+    ronda: u32,
+    fecha: u64,
+    semilla: u64,
+    mesa: String,
+    posicion: usize,
+    apuesta: u32,
+    seguro: u32,
+    jugador: String,
+    banca: String,
+    acciones: String,
+    robadas_banca: String,
+    manos: String,
+    resultados: String,
+    saldo_inicial: u32,
+    saldo_final: u32,
+    neto: i64,
+}
+
+pub fn exportar_csv(rondas: &[RondaJugada], destino: impl Write) -> Result<(), String> {
+    let mut escritor = csv::Writer::from_writer(destino);
+    for ronda in rondas {
+        escritor
+            .serialize(fila_csv(ronda))
+            .map_err(|e| e.to_string())?;
+    }
+    escritor.flush().map_err(|e| e.to_string())
+}
+
+fn fila_csv(ronda: &RondaJugada) -> FilaCsv {
+    let acciones = ronda
+        .acciones
+        .iter()
+        .map(|paso| {
+            let mut texto = format!("{}:{}", paso.mano + 1, nombre(&paso.accion));
+            if !paso.cartas.is_empty() {
+                texto = format!("{} {}", texto, cartas(&paso.cartas));
+            }
+            texto
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let manos = ronda
+        .manos
+        .iter()
+        .map(|mano| format!("{} ({})", cartas(&mano.cartas), mano.apuesta))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let resultados = ronda
+        .manos
+        .iter()
+        .map(|mano| nombre(&mano.resultado))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    FilaCsv {
+        ronda: ronda.ronda,
+        fecha: ronda.fecha,
+        semilla: ronda.semilla,
+        mesa: ronda.reglas.nombre.clone(),
+        posicion: ronda.posicion,
+        apuesta: ronda.apuesta,
+        seguro: ronda.seguro,
+        jugador: cartas(&ronda.jugador),
+        banca: cartas(&ronda.banca),
+        acciones,
+        robadas_banca: cartas(&ronda.robadas_banca),
+        manos,
+        resultados,
+        saldo_inicial: ronda.saldo_inicial,
+        saldo_final: ronda.saldo_final,
+        neto: ronda.neto,
+    }
+}
+
+// Cartas como texto, p. ej. "A♤ 10♡"
+fn cartas(cartas: &[Carta]) -> String {
+    cartas
+        .iter()
+        .map(|carta| format!("{}{}", carta.valor_str(), carta.simbolo()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Mismo nombre que en el JSON, p. ej. "banca_se_pasa"
+fn nombre(valor: &impl Serialize) -> String {
+    serde_json::to_value(valor)
+        .ok()
+        .and_then(|valor| valor.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::deck::Palo;
+    use crate::game::logic::{
+        apostar, avanzar_mano, determinar_ganador, jugar_banca, jugar_turno, repartir_cartas,
+    };
+    use crate::game::player::SALDO_INICIAL;
+    use crate::game::rules::ReglasMesa;
+    use crate::game::shoe::Shoe;
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: Palo::Picas,
+        }
+    }
+
+    // Jugador 10-2 contra banca 6-10: pide un 5, se planta y la banca se pasa con un 9
+    fn ronda_de_prueba() -> RondaJugada {
+        let reglas = ReglasMesa::default();
+        // Las cartas se roban desde el final
+        let mut shoe = Shoe::from(vec![
+            carta(9),
+            carta(5),
+            carta(10),
+            carta(2),
+            carta(6),
+            carta(10),
+        ]);
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+
+        assert!(apostar(&mut jugador, 100, &reglas));
+        repartir_cartas(&mut jugador, &mut banca, &mut shoe, &reglas);
+        let mut ronda = RondaJugada::empezar(1, 7, &reglas, 0, SALDO_INICIAL, &jugador, &banca);
+
+        let antes = shoe.repartidas().len();
+        jugar_turno(&mut jugador, &mut shoe, true);
+        ronda.anotar(0, Accion::Pedir, &shoe.repartidas()[antes..]);
+        avanzar_mano(&mut jugador, &mut shoe, &reglas);
+        ronda.anotar(0, Accion::Plantarse, &[]);
+
+        jugar_banca(&jugador, &mut banca, &mut shoe, &reglas);
+        let liquidacion = determinar_ganador(&mut jugador, &mut banca, &reglas);
+        ronda.terminar(&jugador, &banca, &liquidacion);
+        ronda
+    }
+
+    #[test]
+    fn test_registrar_ronda() {
+        let ronda = ronda_de_prueba();
+        assert_eq!(ronda.apuesta, 100);
+        assert_eq!(ronda.jugador, vec![carta(10), carta(2)]);
+        assert_eq!(ronda.banca, vec![carta(6), carta(10)]);
+        assert_eq!(ronda.acciones[0].cartas, vec![carta(5)]);
+        assert_eq!(ronda.robadas_banca, vec![carta(9)]);
+        assert_eq!(ronda.manos[0].resultado, Resultado::BancaSePasa);
+        assert_eq!(ronda.saldo_final, SALDO_INICIAL + 100);
+        assert_eq!(ronda.neto, 100);
+    }
+
+    #[test]
+    fn test_jsonl_ida_y_vuelta() {
+        let ronda = ronda_de_prueba();
+        let mut texto = Vec::new();
+        escribir_jsonl(&mut texto, &ronda).unwrap();
+        escribir_jsonl(&mut texto, &ronda).unwrap();
+
+        let (leidas, descartadas) = leer_jsonl(texto.as_slice()).unwrap();
+        assert_eq!(leidas, vec![ronda.clone(), ronda]);
+        assert_eq!(descartadas, 0);
+        assert_eq!(
+            leer_jsonl("{\"ronda\": 1}".as_bytes()).unwrap(),
+            (Vec::new(), 1)
+        );
+    }
+
+    #[test]
+    fn test_jsonl_con_ultima_linea_cortada() {
+        let ronda = ronda_de_prueba();
+        let mut texto = Vec::new();
+        escribir_jsonl(&mut texto, &ronda).unwrap();
+        escribir_jsonl(&mut texto, &ronda).unwrap();
+        // El juego se cerró a mitad de escribir la segunda ronda
+        texto.truncate(texto.len() - 20);
+
+        let (leidas, descartadas) = leer_jsonl(texto.as_slice()).unwrap();
+        assert_eq!(leidas, vec![ronda]);
+        assert_eq!(descartadas, 1);
+    }
+
+    #[test]
+    fn test_exportar_csv() {
+        let ronda = ronda_de_prueba();
+        let mut texto = Vec::new();
+        exportar_csv(&[ronda], &mut texto).unwrap();
+        let texto = String::from_utf8(texto).unwrap();
+        let lineas: Vec<&str> = texto.lines().collect();
+
+        assert_eq!(lineas.len(), 2);
+        assert!(lineas[0].starts_with("ronda,fecha,semilla,mesa,posicion,apuesta"));
+        assert!(lineas[1].contains("1:pedir 5♤; 1:plantarse"));
+        assert!(lineas[1].contains("banca_se_pasa"));
+        assert!(lineas[1].ends_with(",1000,1100,100"));
+    }
+}
@@ -1,91 +1,525 @@
-use crate::game::deck::Carta;
+use crate::game::hand::Mano;
 use crate::game::player::Jugador;
+use crate::game::rules::{ReglaBanca, ReglaDoblar, ReglaPeek, ReglasMesa, Rendicion};
+use crate::game::shoe::Shoe;
+use serde::{Deserialize, Serialize};
+
+// Desenlace de una mano del jugador
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resultado {
+    Blackjack,
+    Gana,
+    BancaSePasa,
+    Empate,
+    Pierde,
+    SePasa,
+    BlackjackBanca,
+    Rendicion,
+    DineroPar,
+}
+
+impl Resultado {
+    pub fn mensaje(&self) -> &'static str {
+        match self {
+            Resultado::Blackjack => "¡Blackjack!",
+            Resultado::Gana => "¡Has ganado!",
+            Resultado::BancaSePasa => "La banca se ha pasado. ¡Has ganado!",
+            Resultado::Empate => "Empate.",
+            Resultado::Pierde => "La banca gana.",
+            Resultado::SePasa => "Te has pasado. ¡La banca gana!",
+            Resultado::BlackjackBanca => "La banca tiene blackjack.",
+            Resultado::Rendicion => "Te has rendido: recuperas media apuesta.",
+            Resultado::DineroPar => "Dinero par: cobras tu blackjack 1:1.",
+        }
+    }
+}
+
+// Resultados de una ronda ya pagada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Liquidacion {
+    pub resultados: Vec<Resultado>,
+    // Seguro si se había tomado
+    pub seguro: Option<PagoSeguro>,
+}
+
+// Apuesta lateral del seguro y lo que se cobró por ella (0 si se perdió)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PagoSeguro {
+    pub apuesta: u32,
+    pub pago: u32,
+}
+
+impl PagoSeguro {
+    // Ganancia o pérdida del seguro
+    pub fn neto(&self) -> i64 {
+        self.pago as i64 - self.apuesta as i64
+    }
+}
+
+impl Liquidacion {
+    pub fn mensaje(&self) -> String {
+        let manos = if self.resultados.len() == 1 {
+            self.resultados[0].mensaje().to_string()
+        } else {
+            self.resultados
+                .iter()
+                .enumerate()
+                .map(|(i, resultado)| format!("Mano {}: {}", i + 1, resultado.mensaje()))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+
+        match self.seguro {
+            Some(seguro) => format!("{} {}", mensaje_seguro(seguro), manos),
+            None => manos,
+        }
+    }
+}
+
+pub fn mensaje_seguro(seguro: PagoSeguro) -> String {
+    if seguro.pago > 0 {
+        format!("El seguro paga {}.", seguro.neto())
+    } else {
+        "La banca no tiene blackjack: pierdes el seguro.".to_string()
+    }
+}
+
+// Coloca la apuesta inicial de la ronda y la descuenta del saldo
+pub fn apostar(jugador: &mut Jugador, cantidad: u32, reglas: &ReglasMesa) -> bool {
+    if cantidad < reglas.apuesta_minima
+        || cantidad > reglas.apuesta_maxima
+        || cantidad > jugador.saldo
+    {
+        return false;
+    }
+
+    jugador.saldo -= cantidad;
+    jugador.limpiar_manos(cantidad);
+    true
+}
 
 // Función para repartir cartas iniciales
-pub fn repartir_cartas(jugador: &mut Jugador, banca: &mut Jugador, baraja: &mut Vec<Carta>) {
-    // Se reparten dos cartas a cada jugador
-    jugador.tomar_carta(baraja);
-    banca.tomar_carta(baraja);
-    jugador.tomar_carta(baraja);
-    banca.tomar_carta(baraja);
+pub fn repartir_cartas(
+    jugador: &mut Jugador,
+    banca: &mut Jugador,
+    shoe: &mut Shoe,
+    reglas: &ReglasMesa,
+) {
+    // Se reparten dos cartas a cada jugador. Sin carta oculta, la banca
+    // recibe la segunda al empezar su turno
+    jugador.tomar_carta(shoe);
+    banca.tomar_carta(shoe);
+    jugador.tomar_carta(shoe);
+    if !reglas.peek.sin_carta_oculta() {
+        banca.tomar_carta(shoe);
+    }
+}
 
-    // Actualizar puntos
-    jugador.puntos = jugador.puntaje();
-    banca.puntos = banca.puntaje();
+// La banca puede tener blackjack si su carta visible es un as o vale diez
+pub fn banca_puede_tener_blackjack(banca: &Jugador) -> bool {
+    banca
+        .mano()
+        .cartas
+        .first()
+        .is_some_and(|carta| carta.puntos() >= 10)
 }
 
-// Función para jugar un turno
-pub fn jugar_turno(jugador: &mut Jugador, baraja: &mut Vec<Carta>, tomar_carta: bool) {
-    if tomar_carta {
-        jugador.tomar_carta(baraja);
-        jugador.puntos = jugador.puntaje();
-    }
-}
-
-// Función para determinar el ganador
-pub fn determinar_ganador(jugador: &mut Jugador, banca: &mut Jugador) -> String {
-    let puntos_jugador = jugador.puntos;
-    let puntos_banca = banca.puntos;
-
-    let mensaje = if puntos_jugador > 21 {
-        banca.partida_ganada();
-        "Te has pasado. ¡La banca gana!"
-    } else if puntos_banca > 21 {
-        jugador.partida_ganada();
-        "La banca se ha pasado. ¡Has ganado!"
-    } else if puntos_jugador > puntos_banca {
-        jugador.partida_ganada();
-        "¡Has ganado!"
-    } else if puntos_banca > puntos_jugador {
-        banca.partida_ganada();
-        "La banca gana."
+// El seguro se ofrece cuando la carta visible de la banca es un as
+pub fn ofrecer_seguro(banca: &Jugador, reglas: &ReglasMesa) -> bool {
+    reglas.seguro
+        && banca
+            .mano()
+            .cartas
+            .first()
+            .is_some_and(|carta| carta.valor == 1)
+}
+
+// Apuesta lateral de media apuesta contra el blackjack de la banca. No hay medias
+// fichas, así que con una apuesta impar se redondea hacia abajo para no pasar del
+// límite de media apuesta (con 5 se asegura 2, que cobra 4 si la banca tiene blackjack).
+// Si la mitad no llega a una ficha, no se ofrece
+pub fn tomar_seguro(jugador: &mut Jugador) -> bool {
+    let coste = jugador.mano().apuesta / 2;
+    if coste == 0 || jugador.saldo < coste {
+        return false;
+    }
+
+    jugador.saldo -= coste;
+    jugador.seguro = coste;
+    true
+}
+
+// Dinero par: con blackjack frente a un as, se cobra 1:1 sin esperar a la banca
+pub fn dinero_par(jugador: &mut Jugador) -> Option<Liquidacion> {
+    if !jugador.mano().es_blackjack() {
+        return None;
+    }
+
+    let mano = jugador.mano_mut();
+    mano.terminada = true;
+    let pago = mano.apuesta * 2;
+    jugador.saldo += pago;
+    jugador.partida_ganada();
+    Some(Liquidacion {
+        resultados: vec![Resultado::DineroPar],
+        seguro: None,
+    })
+}
+
+// Liquida el seguro en cuanto se conoce la segunda carta de la banca. Paga 2:1.
+// Devuelve la apuesta lateral y lo cobrado por ella, si se había tomado
+pub fn resolver_seguro(jugador: &mut Jugador, banca: &Jugador) -> Option<PagoSeguro> {
+    if jugador.seguro == 0 {
+        return None;
+    }
+
+    let seguro = jugador.seguro;
+    jugador.seguro = 0;
+    let pago = if banca.mano().es_blackjack() {
+        seguro * 3
     } else {
-        "Empate."
+        0
     };
-    mensaje.to_string()
+    jugador.saldo += pago;
+    Some(PagoSeguro {
+        apuesta: seguro,
+        pago,
+    })
+}
+
+// La rendición solo se permite como primera decisión de la mano.
+// Antes del peek solo cabe la rendición temprana
+pub fn puede_rendirse(jugador: &Jugador, reglas: &ReglasMesa, antes_del_peek: bool) -> bool {
+    let mano = jugador.mano();
+    let permitida = match reglas.rendicion {
+        Rendicion::No => false,
+        Rendicion::Tardia => !antes_del_peek,
+        Rendicion::Temprana => true,
+    };
+
+    permitida
+        && jugador.manos.len() == 1
+        && mano.cartas.len() == 2
+        && !mano.terminada
+        && !mano.es_blackjack()
+}
+
+// Función para rendirse: se abandona la mano y se recupera media apuesta al liquidar
+pub fn rendirse(jugador: &mut Jugador, reglas: &ReglasMesa, antes_del_peek: bool) -> bool {
+    if !puede_rendirse(jugador, reglas, antes_del_peek) {
+        return false;
+    }
+
+    let mano = jugador.mano_mut();
+    mano.rendida = true;
+    mano.terminada = true;
+    true
+}
+
+// Revisa los blackjacks tras el reparto. Devuelve el resultado si la ronda
+// se liquida inmediatamente
+pub fn revisar_blackjack(
+    jugador: &mut Jugador,
+    banca: &mut Jugador,
+    reglas: &ReglasMesa,
+) -> Option<Liquidacion> {
+    // Con carta oculta, la banca mira si tiene blackjack (peek)
+    if !reglas.peek.sin_carta_oculta()
+        && banca_puede_tener_blackjack(banca)
+        && banca.mano().es_blackjack()
+    {
+        return Some(determinar_ganador(jugador, banca, reglas));
+    }
+
+    if jugador.mano().es_blackjack() {
+        jugador.mano_mut().terminada = true;
+        // Sin carta oculta, hay que esperar a la segunda carta de la banca
+        if reglas.peek.sin_carta_oculta() && banca_puede_tener_blackjack(banca) {
+            return None;
+        }
+        return Some(determinar_ganador(jugador, banca, reglas));
+    }
+    None
+}
+
+// Función para jugar un turno
+pub fn jugar_turno(jugador: &mut Jugador, shoe: &mut Shoe, tomar_carta: bool) {
+    if tomar_carta {
+        jugador.tomar_carta(shoe);
+    }
+}
+
+// Los ases divididos no admiten más cartas
+pub fn puede_pedir(jugador: &Jugador) -> bool {
+    let mano = jugador.mano();
+    !mano.terminada && !mano.es_as_dividido()
+}
+
+// Comprueba si el jugador puede doblar su mano con las reglas de la mesa
+pub fn puede_doblar(jugador: &Jugador, reglas: &ReglasMesa) -> bool {
+    let mano = jugador.mano();
+    if mano.cartas.len() != 2 || mano.doblada || mano.es_as_dividido() {
+        return false;
+    }
+    if jugador.saldo < mano.apuesta {
+        return false;
+    }
+    if mano.dividida && !reglas.doblar_tras_dividir {
+        return false;
+    }
+
+    let total = mano.puntaje();
+    match reglas.doblar {
+        ReglaDoblar::Cualquiera => true,
+        ReglaDoblar::NueveAOnce => (9..=11).contains(&total),
+        ReglaDoblar::DiezAOnce => (10..=11).contains(&total),
+    }
+}
+
+// Función para doblar: se dobla la apuesta, se toma una única carta y termina la mano
+pub fn doblar(jugador: &mut Jugador, shoe: &mut Shoe, reglas: &ReglasMesa) -> bool {
+    if !puede_doblar(jugador, reglas) {
+        return false;
+    }
+
+    let apuesta = jugador.mano().apuesta;
+    jugador.saldo -= apuesta;
+    let mano = jugador.mano_mut();
+    mano.apuesta += apuesta;
+    mano.doblada = true;
+    jugar_turno(jugador, shoe, true);
+    avanzar_mano(jugador, shoe, reglas);
+    true
+}
+
+// Comprueba si la mano activa es una pareja que se puede dividir
+pub fn puede_dividir(jugador: &Jugador, reglas: &ReglasMesa) -> bool {
+    let mano = jugador.mano();
+    if !mano.es_par() || mano.doblada || jugador.manos.len() >= reglas.max_manos {
+        return false;
+    }
+    if jugador.saldo < mano.apuesta {
+        return false;
+    }
+    if mano.es_as_dividido() && !reglas.redividir_ases {
+        return false;
+    }
+    true
+}
+
+// Función para dividir una pareja en dos manos con la misma apuesta
+pub fn dividir(jugador: &mut Jugador, shoe: &mut Shoe, reglas: &ReglasMesa) -> bool {
+    if !puede_dividir(jugador, reglas) {
+        return false;
+    }
+
+    let activa = jugador.mano_activa;
+    jugador.saldo -= jugador.manos[activa].apuesta;
+    let mano = &mut jugador.manos[activa];
+    let mut nueva = Mano::nueva(mano.apuesta);
+    nueva.cartas.extend(mano.cartas.pop());
+    nueva.puntos = nueva.puntaje();
+    nueva.dividida = true;
+    mano.dividida = true;
+    jugador.manos.insert(activa + 1, nueva);
+
+    completar_mano_dividida(jugador, shoe, reglas);
+    if jugador.mano().terminada {
+        avanzar_mano(jugador, shoe, reglas);
+    }
+    true
+}
+
+// Termina la mano activa y pasa a la siguiente pendiente. Devuelve false si no quedan manos
+pub fn avanzar_mano(jugador: &mut Jugador, shoe: &mut Shoe, reglas: &ReglasMesa) -> bool {
+    jugador.mano_mut().terminada = true;
+
+    while let Some(siguiente) = jugador.manos.iter().position(|mano| !mano.terminada) {
+        jugador.mano_activa = siguiente;
+        completar_mano_dividida(jugador, shoe, reglas);
+        if !jugador.mano().terminada {
+            return true;
+        }
+    }
+    false
+}
+
+// Una mano dividida recibe su segunda carta al empezar a jugarse.
+// Los ases divididos solo reciben esa carta, salvo que se puedan volver a dividir
+fn completar_mano_dividida(jugador: &mut Jugador, shoe: &mut Shoe, reglas: &ReglasMesa) {
+    if jugador.mano().cartas.len() == 1 {
+        jugador.tomar_carta(shoe);
+    }
+    if jugador.mano().es_as_dividido() && !puede_dividir(jugador, reglas) {
+        jugador.mano_mut().terminada = true;
+    }
+}
+
+// La banca pide por debajo de 17, y con 17 blando si la mesa es H17
+pub fn banca_debe_pedir(mano: &Mano, reglas: &ReglasMesa) -> bool {
+    let valor = mano.valor();
+    valor.total < 17 || (valor.total == 17 && valor.blanda && reglas.banca == ReglaBanca::H17)
+}
+
+// Turno automático de la banca
+pub fn jugar_banca(jugador: &Jugador, banca: &mut Jugador, shoe: &mut Shoe, reglas: &ReglasMesa) {
+    // Sin carta oculta, la banca recibe ahora su segunda carta
+    if banca.mano().cartas.len() == 1 {
+        banca.tomar_carta(shoe);
+    }
+
+    while jugador.necesita_banca() && banca_debe_pedir(banca.mano(), reglas) {
+        banca.tomar_carta(shoe);
+    }
+}
+
+// Función para determinar el ganador de cada mano del jugador y pagar las apuestas
+pub fn determinar_ganador(
+    jugador: &mut Jugador,
+    banca: &mut Jugador,
+    reglas: &ReglasMesa,
+) -> Liquidacion {
+    let puntos_banca = banca.mano().puntos;
+    let blackjack_banca = banca.mano().es_blackjack();
+    let mut resultados = Vec::new();
+    let seguro = resolver_seguro(jugador, banca);
+
+    // Sin carta oculta, la rendición tardía no protege ante un blackjack de la banca
+    let rendicion_anulada =
+        blackjack_banca && reglas.rendicion == Rendicion::Tardia && reglas.peek.sin_carta_oculta();
+
+    for i in 0..jugador.manos.len() {
+        let mano = &jugador.manos[i];
+        let puntos_jugador = mano.puntos;
+        let apuesta = mano.apuesta;
+        let blackjack_jugador = mano.es_blackjack();
+
+        // Lo que recibe el jugador, incluida su apuesta
+        let (resultado, pago) = if mano.rendida && !rendicion_anulada {
+            banca.partida_ganada();
+            (Resultado::Rendicion, apuesta / 2)
+        } else if puntos_jugador > 21 {
+            banca.partida_ganada();
+            (Resultado::SePasa, 0)
+        } else if blackjack_jugador && !blackjack_banca {
+            jugador.partida_ganada();
+            (
+                Resultado::Blackjack,
+                apuesta + reglas.pago_blackjack.ganancia(apuesta),
+            )
+        } else if blackjack_banca && !blackjack_jugador {
+            banca.partida_ganada();
+            if reglas.peek == ReglaPeek::EuropeaApuestaOriginal {
+                // Solo se pierde la apuesta original: se devuelven dobles y divisiones
+                let devolucion = if i > 0 {
+                    apuesta
+                } else if mano.doblada {
+                    apuesta / 2
+                } else {
+                    0
+                };
+                (Resultado::BlackjackBanca, devolucion)
+            } else {
+                (Resultado::BlackjackBanca, 0)
+            }
+        } else if puntos_banca > 21 {
+            jugador.partida_ganada();
+            (Resultado::BancaSePasa, apuesta * 2)
+        } else if puntos_jugador > puntos_banca {
+            jugador.partida_ganada();
+            (Resultado::Gana, apuesta * 2)
+        } else if puntos_banca > puntos_jugador {
+            banca.partida_ganada();
+            (Resultado::Pierde, 0)
+        } else {
+            (Resultado::Empate, apuesta)
+        };
+        jugador.saldo += pago;
+        resultados.push(resultado);
+    }
+
+    Liquidacion { resultados, seguro }
+}
+
+// Función para reiniciar la partida: las cartas van a la bandeja y el shoe se conserva.
+// Devuelve true si se ha llegado a la carta de corte y se ha barajado
+pub fn reiniciar_partida(jugador: &mut Jugador, banca: &mut Jugador, shoe: &mut Shoe) -> bool {
+    for mano in jugador.manos.iter().chain(banca.manos.iter()) {
+        shoe.descartar(mano.cartas.iter().copied());
+    }
+    jugador.limpiar_manos(0);
+    banca.limpiar_manos(0);
+
+    shoe.terminar_ronda()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::game::deck::crear_baraja;
-    use crate::game::player::Jugador;
+    use crate::game::deck::{Carta, Palo};
+    use crate::game::player::{Jugador, SALDO_INICIAL};
+    use crate::game::rules::{PagoBlackjack, PerfilMesa, TipoShoe};
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: Palo::Picas,
+        }
+    }
 
     #[test]
     fn test_repartir_cartas() {
-        let mut baraja = crear_baraja();
+        let mut shoe = Shoe::nuevo(1, 1.0, TipoShoe::CartaCorte);
         let mut jugador = Jugador::nuevo();
         let mut banca = Jugador::nuevo();
 
-        repartir_cartas(&mut jugador, &mut banca, &mut baraja);
+        repartir_cartas(&mut jugador, &mut banca, &mut shoe, &ReglasMesa::default());
 
-        assert_eq!(jugador.mano.len(), 2);
-        assert_eq!(banca.mano.len(), 2);
-        assert_eq!(baraja.len(), 48); // 52 - 4 cartas repartidas
+        assert_eq!(jugador.mano().cartas.len(), 2);
+        assert_eq!(banca.mano().cartas.len(), 2);
+        assert_eq!(shoe.restantes(), 48); // 52 - 4 cartas repartidas
+    }
+
+    #[test]
+    fn test_repartir_cartas_sin_carta_oculta() {
+        let mut shoe = Shoe::nuevo(1, 1.0, TipoShoe::CartaCorte);
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        let reglas = ReglasMesa {
+            peek: ReglaPeek::Europea,
+            ..ReglasMesa::default()
+        };
+
+        repartir_cartas(&mut jugador, &mut banca, &mut shoe, &reglas);
+
+        assert_eq!(jugador.mano().cartas.len(), 2);
+        assert_eq!(banca.mano().cartas.len(), 1);
+        assert_eq!(shoe.restantes(), 49);
     }
 
     #[test]
     fn test_jugar_turno() {
-        let mut baraja = crear_baraja();
+        let mut shoe = Shoe::nuevo(1, 1.0, TipoShoe::CartaCorte);
         let mut jugador = Jugador::nuevo();
 
-        jugar_turno(&mut jugador, &mut baraja, true);
-        assert_eq!(jugador.mano.len(), 1);
+        jugar_turno(&mut jugador, &mut shoe, true);
+        assert_eq!(jugador.mano().cartas.len(), 1);
 
-        jugar_turno(&mut jugador, &mut baraja, false);
-        assert_eq!(jugador.mano.len(), 1); // No debe cambiar si no toma carta
+        jugar_turno(&mut jugador, &mut shoe, false);
+        assert_eq!(jugador.mano().cartas.len(), 1); // No debe cambiar si no toma carta
     }
 
     #[test]
     fn test_determinar_ganador_jugador_pasa() {
         let mut jugador = Jugador::nuevo();
         let mut banca = Jugador::nuevo();
-        jugador.puntos = 22;
-        banca.puntos = 18;
+        jugador.mano_mut().puntos = 22;
+        banca.mano_mut().puntos = 18;
 
         assert_eq!(
-            determinar_ganador(&mut jugador, &mut banca),
-            "Te has pasado. ¡La banca gana!"
+            determinar_ganador(&mut jugador, &mut banca, &ReglasMesa::default()).resultados,
+            vec![Resultado::SePasa]
         );
     }
 
@@ -93,12 +527,12 @@ mod tests {
     fn test_determinar_ganador_banca_pasa() {
         let mut jugador = Jugador::nuevo();
         let mut banca = Jugador::nuevo();
-        jugador.puntos = 18;
-        banca.puntos = 22;
+        jugador.mano_mut().puntos = 18;
+        banca.mano_mut().puntos = 22;
 
         assert_eq!(
-            determinar_ganador(&mut jugador, &mut banca),
-            "La banca se ha pasado. ¡Has ganado!"
+            determinar_ganador(&mut jugador, &mut banca, &ReglasMesa::default()).resultados,
+            vec![Resultado::BancaSePasa]
         );
     }
 
@@ -106,22 +540,25 @@ mod tests {
     fn test_determinar_ganador_jugador_gana() {
         let mut jugador = Jugador::nuevo();
         let mut banca = Jugador::nuevo();
-        jugador.puntos = 20;
-        banca.puntos = 18;
+        jugador.mano_mut().puntos = 20;
+        banca.mano_mut().puntos = 18;
 
-        assert_eq!(determinar_ganador(&mut jugador, &mut banca), "¡Has ganado!");
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &ReglasMesa::default()).resultados,
+            vec![Resultado::Gana]
+        );
     }
 
     #[test]
     fn test_determinar_ganador_banca_gana() {
         let mut jugador = Jugador::nuevo();
         let mut banca = Jugador::nuevo();
-        jugador.puntos = 17;
-        banca.puntos = 20;
+        jugador.mano_mut().puntos = 17;
+        banca.mano_mut().puntos = 20;
 
         assert_eq!(
-            determinar_ganador(&mut jugador, &mut banca),
-            "La banca gana."
+            determinar_ganador(&mut jugador, &mut banca, &ReglasMesa::default()).resultados,
+            vec![Resultado::Pierde]
         );
     }
 
@@ -129,9 +566,692 @@ mod tests {
     fn test_determinar_ganador_empate() {
         let mut jugador = Jugador::nuevo();
         let mut banca = Jugador::nuevo();
-        jugador.puntos = 19;
-        banca.puntos = 19;
+        jugador.mano_mut().puntos = 19;
+        banca.mano_mut().puntos = 19;
+
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &ReglasMesa::default()).resultados,
+            vec![Resultado::Empate]
+        );
+    }
+
+    #[test]
+    fn test_doblar_toma_una_carta_y_dobla_apuesta() {
+        let mut shoe = Shoe::from(vec![carta(10), carta(9)]);
+        let mut jugador = Jugador::nuevo();
+        let reglas = ReglasMesa::default();
+        assert!(apostar(&mut jugador, 10, &reglas));
+        jugador.mano_mut().cartas = vec![carta(6), carta(5)];
+
+        assert!(doblar(&mut jugador, &mut shoe, &reglas));
+        assert_eq!(jugador.mano().cartas.len(), 3);
+        assert_eq!(jugador.mano().apuesta, 20);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 20);
+        assert_eq!(jugador.mano().puntos, 20);
+        assert!(jugador.mano().doblada);
+        assert!(jugador.mano().terminada);
+        assert_eq!(shoe.restantes(), 1);
+
+        // No se puede volver a doblar la misma mano
+        assert!(!doblar(&mut jugador, &mut shoe, &reglas));
+        assert_eq!(jugador.mano().cartas.len(), 3);
+    }
+
+    #[test]
+    fn test_doblar_solo_con_dos_cartas() {
+        let mut jugador = Jugador::nuevo();
+        jugador.mano_mut().cartas = vec![carta(2), carta(3), carta(4)];
+
+        assert!(!puede_doblar(&jugador, &ReglasMesa::default()));
+    }
+
+    #[test]
+    fn test_doblar_nueve_a_once() {
+        let reglas = ReglasMesa {
+            doblar: ReglaDoblar::NueveAOnce,
+            ..ReglasMesa::default()
+        };
+        let mut jugador = Jugador::nuevo();
+
+        jugador.mano_mut().cartas = vec![carta(4), carta(5)];
+        assert!(puede_doblar(&jugador, &reglas));
+        jugador.mano_mut().cartas = vec![carta(5), carta(6)];
+        assert!(puede_doblar(&jugador, &reglas));
+        jugador.mano_mut().cartas = vec![carta(4), carta(4)];
+        assert!(!puede_doblar(&jugador, &reglas));
+        jugador.mano_mut().cartas = vec![carta(1), carta(7)];
+        assert!(!puede_doblar(&jugador, &reglas));
+    }
+
+    #[test]
+    fn test_doblar_diez_a_once() {
+        let reglas = ReglasMesa {
+            doblar: ReglaDoblar::DiezAOnce,
+            ..ReglasMesa::default()
+        };
+        let mut jugador = Jugador::nuevo();
+
+        jugador.mano_mut().cartas = vec![carta(4), carta(5)];
+        assert!(!puede_doblar(&jugador, &reglas));
+        jugador.mano_mut().cartas = vec![carta(4), carta(6)];
+        assert!(puede_doblar(&jugador, &reglas));
+        jugador.mano_mut().cartas = vec![carta(13), carta(1)];
+        assert!(!puede_doblar(&jugador, &reglas));
+    }
+
+    #[test]
+    fn test_doblar_tras_dividir() {
+        let mut jugador = Jugador::nuevo();
+        jugador.mano_mut().cartas = vec![carta(5), carta(6)];
+
+        let sin_das = ReglasMesa {
+            doblar_tras_dividir: false,
+            ..ReglasMesa::default()
+        };
+        assert!(puede_doblar(&jugador, &sin_das));
+
+        jugador.mano_mut().dividida = true;
+        assert!(!puede_doblar(&jugador, &sin_das));
+        assert!(puede_doblar(&jugador, &ReglasMesa::default()));
+    }
+
+    #[test]
+    fn test_determinar_ganador_varias_manos() {
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        jugador.manos = vec![Mano::nueva(1), Mano::nueva(1)];
+        jugador.manos[0].puntos = 20;
+        jugador.manos[1].puntos = 23;
+        banca.mano_mut().puntos = 18;
+
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &ReglasMesa::default()).mensaje(),
+            "Mano 1: ¡Has ganado! | Mano 2: Te has pasado. ¡La banca gana!"
+        );
+        assert_eq!(jugador.partidas_ganadas, 1);
+        assert_eq!(banca.partidas_ganadas, 1);
+    }
+
+    #[test]
+    fn test_dividir_pareja() {
+        // Se roba desde el final: la primera mano recibe el 3
+        let mut shoe = Shoe::from(vec![carta(10), carta(3)]);
+        let mut jugador = Jugador::nuevo();
+        let reglas = ReglasMesa::default();
+        assert!(apostar(&mut jugador, 10, &reglas));
+        jugador.mano_mut().cartas = vec![carta(8), carta(8)];
+
+        assert!(dividir(&mut jugador, &mut shoe, &reglas));
+        assert_eq!(jugador.manos.len(), 2);
+        assert_eq!(jugador.mano_activa, 0);
+        assert_eq!(jugador.manos[0].puntos, 11);
+        assert_eq!(jugador.manos[1].cartas.len(), 1);
+        assert_eq!(jugador.manos[1].apuesta, 10);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 20);
+        assert!(jugador.manos.iter().all(|mano| mano.dividida));
+
+        // Al plantarse la primera mano, la segunda recibe su carta
+        assert!(avanzar_mano(&mut jugador, &mut shoe, &reglas));
+        assert_eq!(jugador.mano_activa, 1);
+        assert_eq!(jugador.manos[1].puntos, 18);
+        assert!(!avanzar_mano(&mut jugador, &mut shoe, &reglas));
+    }
+
+    #[test]
+    fn test_no_dividir_sin_pareja() {
+        let mut shoe = Shoe::from(vec![carta(2)]);
+        let mut jugador = Jugador::nuevo();
+        jugador.mano_mut().cartas = vec![carta(8), carta(9)];
+
+        assert!(!dividir(&mut jugador, &mut shoe, &ReglasMesa::default()));
+        assert_eq!(jugador.manos.len(), 1);
+    }
+
+    #[test]
+    fn test_limite_de_redivisiones() {
+        let mut shoe = Shoe::from(vec![carta(8), carta(8), carta(8)]);
+        let mut jugador = Jugador::nuevo();
+        jugador.mano_mut().cartas = vec![carta(8), carta(8)];
+        let reglas = ReglasMesa {
+            max_manos: 2,
+            ..ReglasMesa::default()
+        };
+
+        assert!(dividir(&mut jugador, &mut shoe, &reglas));
+        assert!(jugador.mano().es_par());
+        assert!(!puede_dividir(&jugador, &reglas));
+        assert!(!dividir(&mut jugador, &mut shoe, &reglas));
+        assert_eq!(jugador.manos.len(), 2);
+    }
+
+    #[test]
+    fn test_ases_divididos_reciben_una_carta() {
+        let mut shoe = Shoe::from(vec![carta(9), carta(5)]);
+        let mut jugador = Jugador::nuevo();
+        jugador.mano_mut().cartas = vec![carta(1), carta(1)];
+        let reglas = ReglasMesa::default();
+
+        assert!(dividir(&mut jugador, &mut shoe, &reglas));
+        assert_eq!(shoe.restantes(), 0);
+        assert_eq!(jugador.manos[0].puntos, 16);
+        assert_eq!(jugador.manos[1].puntos, 20);
+        assert!(jugador.manos.iter().all(|mano| mano.terminada));
+        assert!(!puede_pedir(&jugador));
+    }
+
+    #[test]
+    fn test_redividir_ases() {
+        let mut shoe = Shoe::from(vec![carta(7), carta(6), carta(1)]);
+        let mut jugador = Jugador::nuevo();
+        jugador.mano_mut().cartas = vec![carta(1), carta(1)];
+
+        // Sin redividir ases, el nuevo as se queda en la mano
+        let mut sin_redividir = Jugador::nuevo();
+        sin_redividir.mano_mut().cartas = vec![carta(1), carta(1)];
+        let mut otro_shoe = shoe.clone();
+        dividir(&mut sin_redividir, &mut otro_shoe, &ReglasMesa::default());
+        assert!(sin_redividir.manos[0].terminada);
+        assert_eq!(sin_redividir.manos.len(), 2);
+
+        let reglas = ReglasMesa {
+            redividir_ases: true,
+            ..ReglasMesa::default()
+        };
+        assert!(dividir(&mut jugador, &mut shoe, &reglas));
+        assert!(!jugador.mano().terminada);
+        assert!(!puede_pedir(&jugador));
+        assert!(!puede_doblar(&jugador, &reglas));
+        assert!(dividir(&mut jugador, &mut shoe, &reglas));
+        assert_eq!(jugador.manos.len(), 3);
+        assert!(jugador.manos.iter().all(|mano| mano.terminada));
+    }
+
+    #[test]
+    fn test_apostar_respeta_limites_y_saldo() {
+        let reglas = ReglasMesa::default();
+        let mut jugador = Jugador::nuevo();
+
+        assert!(!apostar(&mut jugador, reglas.apuesta_minima - 1, &reglas));
+        assert!(!apostar(&mut jugador, reglas.apuesta_maxima + 1, &reglas));
+        assert_eq!(jugador.saldo, SALDO_INICIAL);
+
+        assert!(apostar(&mut jugador, 50, &reglas));
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 50);
+        assert_eq!(jugador.mano().apuesta, 50);
+
+        jugador.saldo = 20;
+        assert!(!apostar(&mut jugador, 30, &reglas));
+    }
+
+    #[test]
+    fn test_no_doblar_sin_saldo() {
+        let reglas = ReglasMesa::default();
+        let mut jugador = Jugador::nuevo();
+        assert!(apostar(&mut jugador, 100, &reglas));
+        jugador.mano_mut().cartas = vec![carta(5), carta(5)];
+
+        jugador.saldo = 99;
+        assert!(!puede_doblar(&jugador, &reglas));
+        assert!(!puede_dividir(&jugador, &reglas));
+        jugador.saldo = 100;
+        assert!(puede_doblar(&jugador, &reglas));
+        assert!(puede_dividir(&jugador, &reglas));
+    }
+
+    #[test]
+    fn test_pagos() {
+        let reglas = ReglasMesa::default();
+        let mut banca = Jugador::nuevo();
+        banca.mano_mut().cartas = vec![carta(10), carta(8)];
+        banca.mano_mut().puntos = 18;
+
+        // Victoria 1:1
+        let mut jugador = Jugador::nuevo();
+        apostar(&mut jugador, 10, &reglas);
+        jugador.mano_mut().cartas = vec![carta(10), carta(9)];
+        jugador.mano_mut().puntos = 19;
+        determinar_ganador(&mut jugador, &mut banca, &reglas);
+        assert_eq!(jugador.saldo, SALDO_INICIAL + 10);
+
+        // Empate: se devuelve la apuesta
+        let mut jugador = Jugador::nuevo();
+        apostar(&mut jugador, 10, &reglas);
+        jugador.mano_mut().cartas = vec![carta(10), carta(8)];
+        jugador.mano_mut().puntos = 18;
+        determinar_ganador(&mut jugador, &mut banca, &reglas);
+        assert_eq!(jugador.saldo, SALDO_INICIAL);
+
+        // Pasarse pierde la apuesta
+        let mut jugador = Jugador::nuevo();
+        apostar(&mut jugador, 10, &reglas);
+        jugador.mano_mut().cartas = vec![carta(10), carta(8), carta(5)];
+        jugador.mano_mut().puntos = 23;
+        determinar_ganador(&mut jugador, &mut banca, &reglas);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 10);
+    }
+
+    #[test]
+    fn test_pago_blackjack_natural() {
+        let mut banca = Jugador::nuevo();
+        banca.mano_mut().cartas = vec![carta(10), carta(8)];
+        banca.mano_mut().puntos = 18;
+
+        let reglas = ReglasMesa::default();
+        let mut jugador = Jugador::nuevo();
+        apostar(&mut jugador, 20, &reglas);
+        jugador.mano_mut().cartas = vec![carta(1), carta(13)];
+        jugador.mano_mut().puntos = 21;
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &reglas).resultados,
+            vec![Resultado::Blackjack]
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL + 30);
+
+        let seis_a_cinco = ReglasMesa {
+            pago_blackjack: PagoBlackjack::SeisACinco,
+            ..ReglasMesa::default()
+        };
+        let mut jugador = Jugador::nuevo();
+        apostar(&mut jugador, 20, &seis_a_cinco);
+        jugador.mano_mut().cartas = vec![carta(1), carta(13)];
+        jugador.mano_mut().puntos = 21;
+        determinar_ganador(&mut jugador, &mut banca, &seis_a_cinco);
+        assert_eq!(jugador.saldo, SALDO_INICIAL + 24);
+    }
+
+    // Prepara una ronda con las cartas indicadas y una apuesta de 10
+    fn ronda(
+        cartas_jugador: &[u8],
+        cartas_banca: &[u8],
+        reglas: &ReglasMesa,
+    ) -> (Jugador, Jugador) {
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        apostar(&mut jugador, 10, reglas);
+        jugador.mano_mut().cartas = cartas_jugador.iter().map(|&v| carta(v)).collect();
+        banca.mano_mut().cartas = cartas_banca.iter().map(|&v| carta(v)).collect();
+        jugador.mano_mut().puntos = jugador.mano().puntaje();
+        banca.mano_mut().puntos = banca.mano().puntaje();
+        (jugador, banca)
+    }
+
+    #[test]
+    fn test_blackjack_gana_a_21_de_tres_cartas() {
+        let reglas = ReglasMesa::default();
+
+        let (mut jugador, mut banca) = ronda(&[1, 10], &[7, 4, 10], &reglas);
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &reglas).resultados,
+            vec![Resultado::Blackjack]
+        );
+
+        let (mut jugador, mut banca) = ronda(&[7, 4, 10], &[1, 10], &reglas);
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &reglas).resultados,
+            vec![Resultado::BlackjackBanca]
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 10);
+    }
+
+    #[test]
+    fn test_peek_banca_con_blackjack() {
+        let reglas = ReglasMesa::default();
+        let (mut jugador, mut banca) = ronda(&[10, 9], &[1, 13], &reglas);
+
+        assert!(banca_puede_tener_blackjack(&banca));
+        assert_eq!(
+            revisar_blackjack(&mut jugador, &mut banca, &reglas).map(|l| l.resultados),
+            Some(vec![Resultado::BlackjackBanca])
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 10);
+    }
+
+    #[test]
+    fn test_peek_sin_blackjack_sigue_la_ronda() {
+        let reglas = ReglasMesa::default();
+        let (mut jugador, mut banca) = ronda(&[10, 9], &[10, 7], &reglas);
+
+        assert_eq!(revisar_blackjack(&mut jugador, &mut banca, &reglas), None);
+        assert!(!jugador.mano().terminada);
+    }
+
+    #[test]
+    fn test_blackjack_contra_blackjack_es_empate() {
+        let reglas = ReglasMesa::default();
+        let (mut jugador, mut banca) = ronda(&[1, 12], &[10, 1], &reglas);
+
+        assert_eq!(
+            revisar_blackjack(&mut jugador, &mut banca, &reglas).map(|l| l.resultados),
+            Some(vec![Resultado::Empate])
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL);
+    }
+
+    #[test]
+    fn test_blackjack_del_jugador_se_paga_al_momento() {
+        let reglas = ReglasMesa::default();
+        let (mut jugador, mut banca) = ronda(&[1, 12], &[9, 7], &reglas);
+
+        assert_eq!(
+            revisar_blackjack(&mut jugador, &mut banca, &reglas).map(|l| l.resultados),
+            Some(vec![Resultado::Blackjack])
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL + 15);
+    }
+
+    #[test]
+    fn test_sin_carta_oculta_espera_a_la_banca() {
+        let reglas = ReglasMesa {
+            peek: ReglaPeek::Europea,
+            ..ReglasMesa::default()
+        };
+
+        // Con un diez visible la banca aún puede tener blackjack
+        let (mut jugador, mut banca) = ronda(&[1, 12], &[10], &reglas);
+        assert_eq!(revisar_blackjack(&mut jugador, &mut banca, &reglas), None);
+        assert!(jugador.turno_terminado());
+
+        // Con un 9 visible el blackjack se paga de inmediato
+        let (mut jugador, mut banca) = ronda(&[1, 12], &[9], &reglas);
+        assert_eq!(
+            revisar_blackjack(&mut jugador, &mut banca, &reglas).map(|l| l.resultados),
+            Some(vec![Resultado::Blackjack])
+        );
+    }
+
+    #[test]
+    fn test_sin_carta_oculta_se_pierden_dobles_y_divisiones() {
+        let reglas = ReglasMesa {
+            peek: ReglaPeek::Europea,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, mut banca) = ronda(&[8, 8], &[10], &reglas);
+        let mut shoe = Shoe::from(vec![carta(2), carta(3), carta(3)]);
+        dividir(&mut jugador, &mut shoe, &reglas);
+        doblar(&mut jugador, &mut shoe, &reglas);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 30);
+
+        banca.mano_mut().cartas.push(carta(1));
+        banca.mano_mut().puntos = banca.mano().puntaje();
+        determinar_ganador(&mut jugador, &mut banca, &reglas);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 30);
+    }
+
+    #[test]
+    fn test_sin_carta_oculta_solo_apuesta_original() {
+        let reglas = ReglasMesa {
+            peek: ReglaPeek::EuropeaApuestaOriginal,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, mut banca) = ronda(&[8, 8], &[10], &reglas);
+        let mut shoe = Shoe::from(vec![carta(2), carta(3), carta(3)]);
+        dividir(&mut jugador, &mut shoe, &reglas);
+        doblar(&mut jugador, &mut shoe, &reglas);
+
+        banca.mano_mut().cartas.push(carta(1));
+        banca.mano_mut().puntos = banca.mano().puntaje();
+        determinar_ganador(&mut jugador, &mut banca, &reglas);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 10);
+    }
+
+    #[test]
+    fn test_ofrecer_seguro_solo_con_as() {
+        let reglas = ReglasMesa::default();
+        let (_, banca) = ronda(&[10, 9], &[1, 5], &reglas);
+        assert!(ofrecer_seguro(&banca, &reglas));
+
+        let (_, banca) = ronda(&[10, 9], &[10, 1], &reglas);
+        assert!(!ofrecer_seguro(&banca, &reglas));
+
+        let sin_seguro = ReglasMesa {
+            seguro: false,
+            ..ReglasMesa::default()
+        };
+        let (_, banca) = ronda(&[10, 9], &[1, 5], &sin_seguro);
+        assert!(!ofrecer_seguro(&banca, &sin_seguro));
+    }
+
+    #[test]
+    fn test_seguro_paga_dos_a_uno() {
+        let reglas = ReglasMesa::default();
+        let (mut jugador, mut banca) = ronda(&[10, 9], &[1, 13], &reglas);
+
+        assert!(tomar_seguro(&mut jugador));
+        assert_eq!(jugador.seguro, 5);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 15);
+
+        let pago = resolver_seguro(&mut jugador, &banca).unwrap();
+        assert_eq!((pago.apuesta, pago.pago, pago.neto()), (5, 15, 10));
+        assert_eq!(mensaje_seguro(pago), "El seguro paga 10.");
+        determinar_ganador(&mut jugador, &mut banca, &reglas);
+        // Se pierde la apuesta pero el seguro la compensa
+        assert_eq!(jugador.saldo, SALDO_INICIAL);
+    }
+
+    #[test]
+    fn test_seguro_de_apuesta_impar() {
+        let reglas = PerfilMesa::Downtown.reglas();
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        assert!(apostar(&mut jugador, 5, &reglas));
+        jugador.mano_mut().cartas = vec![carta(10), carta(9)];
+        banca.mano_mut().cartas = vec![carta(1), carta(13)];
+        jugador.mano_mut().puntos = jugador.mano().puntaje();
+        banca.mano_mut().puntos = banca.mano().puntaje();
+
+        // La mitad de 5 se redondea hacia abajo: el seguro no pasa de media apuesta
+        assert!(tomar_seguro(&mut jugador));
+        assert_eq!(jugador.seguro, 2);
+        let pago = resolver_seguro(&mut jugador, &banca).unwrap();
+        assert_eq!(pago.neto(), 4);
+        determinar_ganador(&mut jugador, &mut banca, &reglas);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 1);
+
+        // Con una ficha de apuesta no hay media ficha que asegurar
+        jugador.limpiar_manos(1);
+        assert!(!tomar_seguro(&mut jugador));
+    }
+
+    #[test]
+    fn test_seguro_perdido() {
+        let reglas = ReglasMesa::default();
+        let (mut jugador, banca) = ronda(&[10, 9], &[1, 5], &reglas);
+
+        assert!(tomar_seguro(&mut jugador));
+        let pago = resolver_seguro(&mut jugador, &banca).unwrap();
+        assert_eq!((pago.pago, pago.neto()), (0, -5));
+        assert_eq!(jugador.seguro, 0);
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 15);
+        assert_eq!(resolver_seguro(&mut jugador, &banca), None);
+    }
+
+    #[test]
+    fn test_seguro_sin_carta_oculta_se_resuelve_al_final() {
+        let reglas = ReglasMesa {
+            peek: ReglaPeek::Europea,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, mut banca) = ronda(&[10, 9], &[1], &reglas);
+        assert!(tomar_seguro(&mut jugador));
+
+        banca.mano_mut().cartas.push(carta(12));
+        banca.mano_mut().puntos = banca.mano().puntaje();
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &reglas).mensaje(),
+            "El seguro paga 10. La banca tiene blackjack."
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL);
+    }
+
+    #[test]
+    fn test_dinero_par() {
+        let reglas = ReglasMesa::default();
+        let (mut jugador, _) = ronda(&[1, 13], &[1, 9], &reglas);
+
+        assert_eq!(
+            dinero_par(&mut jugador).map(|l| l.resultados),
+            Some(vec![Resultado::DineroPar])
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL + 10);
+        assert!(jugador.turno_terminado());
+
+        let (mut jugador, _) = ronda(&[10, 9], &[1, 9], &reglas);
+        assert!(dinero_par(&mut jugador).is_none());
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 10);
+    }
+
+    #[test]
+    fn test_mensaje_liquidacion() {
+        let liquidacion = Liquidacion {
+            resultados: vec![Resultado::Gana],
+            seguro: Some(PagoSeguro {
+                apuesta: 5,
+                pago: 0,
+            }),
+        };
+        assert_eq!(
+            liquidacion.mensaje(),
+            "La banca no tiene blackjack: pierdes el seguro. ¡Has ganado!"
+        );
+    }
+
+    #[test]
+    fn test_rendicion_tardia() {
+        let reglas = ReglasMesa {
+            rendicion: Rendicion::Tardia,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, mut banca) = ronda(&[10, 6], &[10, 7], &reglas);
+
+        // La rendición tardía no se ofrece antes del peek
+        assert!(!puede_rendirse(&jugador, &reglas, true));
+        assert!(rendirse(&mut jugador, &reglas, false));
+        assert!(jugador.turno_terminado());
+        assert!(!jugador.necesita_banca());
+
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &reglas).resultados,
+            vec![Resultado::Rendicion]
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 5);
+    }
+
+    #[test]
+    fn test_rendicion_solo_como_primera_decision() {
+        let reglas = ReglasMesa {
+            rendicion: Rendicion::Tardia,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, _) = ronda(&[2, 3, 4], &[10, 7], &reglas);
+        assert!(!puede_rendirse(&jugador, &reglas, false));
+
+        let (mut dividido, _) = ronda(&[8, 8], &[10, 7], &reglas);
+        let mut shoe = Shoe::from(vec![carta(2), carta(3)]);
+        dividir(&mut dividido, &mut shoe, &reglas);
+        assert!(!rendirse(&mut dividido, &reglas, false));
+
+        let sin_rendicion = ReglasMesa {
+            rendicion: Rendicion::No,
+            ..ReglasMesa::default()
+        };
+        jugador.mano_mut().cartas.pop();
+        assert!(!puede_rendirse(&jugador, &sin_rendicion, false));
+    }
+
+    #[test]
+    fn test_rendicion_temprana_ante_blackjack() {
+        let reglas = ReglasMesa {
+            rendicion: Rendicion::Temprana,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, mut banca) = ronda(&[10, 6], &[1, 13], &reglas);
+
+        assert!(rendirse(&mut jugador, &reglas, true));
+        assert_eq!(
+            revisar_blackjack(&mut jugador, &mut banca, &reglas).map(|l| l.resultados),
+            Some(vec![Resultado::Rendicion])
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 5);
+    }
+
+    #[test]
+    fn test_rendicion_tardia_sin_carta_oculta_ante_blackjack() {
+        let reglas = ReglasMesa {
+            rendicion: Rendicion::Tardia,
+            peek: ReglaPeek::Europea,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, mut banca) = ronda(&[10, 6], &[1], &reglas);
+        assert!(rendirse(&mut jugador, &reglas, false));
+
+        banca.mano_mut().cartas.push(carta(10));
+        banca.mano_mut().puntos = banca.mano().puntaje();
+        assert_eq!(
+            determinar_ganador(&mut jugador, &mut banca, &reglas).resultados,
+            vec![Resultado::BlackjackBanca]
+        );
+        assert_eq!(jugador.saldo, SALDO_INICIAL - 10);
+    }
+
+    #[test]
+    fn test_banca_s17_se_planta_con_17_blando() {
+        let reglas = ReglasMesa {
+            banca: ReglaBanca::S17,
+            ..ReglasMesa::default()
+        };
+        let (jugador, mut banca) = ronda(&[10, 8], &[1, 6], &reglas);
+        let mut shoe = Shoe::from(vec![carta(5)]);
+
+        assert!(!banca_debe_pedir(banca.mano(), &reglas));
+        jugar_banca(&jugador, &mut banca, &mut shoe, &reglas);
+        assert_eq!(banca.mano().cartas.len(), 2);
+        assert_eq!(banca.mano().puntaje(), 17);
+    }
+
+    #[test]
+    fn test_banca_h17_pide_con_17_blando() {
+        let reglas = ReglasMesa {
+            banca: ReglaBanca::H17,
+            ..ReglasMesa::default()
+        };
+        let (jugador, mut banca) = ronda(&[10, 8], &[1, 6], &reglas);
+        let mut shoe = Shoe::from(vec![carta(10), carta(3)]);
+
+        assert!(banca_debe_pedir(banca.mano(), &reglas));
+        jugar_banca(&jugador, &mut banca, &mut shoe, &reglas);
+        // A+6+3 = 20 blando
+        assert_eq!(banca.mano().puntaje(), 20);
+        assert_eq!(shoe.restantes(), 1);
+
+        // Con 17 duro se planta en ambas reglas
+        let (_, banca) = ronda(&[10, 8], &[10, 7], &reglas);
+        assert!(!banca_debe_pedir(banca.mano(), &reglas));
+    }
+
+    #[test]
+    fn test_banca_pide_hasta_17() {
+        let reglas = ReglasMesa::default();
+        let (jugador, mut banca) = ronda(&[10, 8], &[10, 2], &reglas);
+        let mut shoe = Shoe::from(vec![carta(9), carta(4), carta(2)]);
+
+        jugar_banca(&jugador, &mut banca, &mut shoe, &reglas);
+        assert_eq!(banca.mano().puntaje(), 18);
+        assert_eq!(shoe.restantes(), 1);
+    }
+
+    #[test]
+    fn test_banca_no_pide_sin_manos_vivas() {
+        let reglas = ReglasMesa {
+            peek: ReglaPeek::Europea,
+            ..ReglasMesa::default()
+        };
+        let (mut jugador, mut banca) = ronda(&[1, 13], &[10], &reglas);
+        jugador.mano_mut().terminada = true;
+        let mut shoe = Shoe::from(vec![carta(5), carta(2)]);
 
-        assert_eq!(determinar_ganador(&mut jugador, &mut banca), "Empate.");
+        // Solo recibe su segunda carta para comprobar el blackjack
+        jugar_banca(&jugador, &mut banca, &mut shoe, &reglas);
+        assert_eq!(banca.mano().cartas.len(), 2);
+        assert_eq!(shoe.restantes(), 1);
     }
 }
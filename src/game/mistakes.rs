@@ -0,0 +1,218 @@
+use crate::game::ev::Evaluador;
+use crate::game::rules::ReglasMesa;
+use crate::game::strategy::{accion_basica, Accion, Opciones, Situacion};
+use serde::{Deserialize, Serialize};
+
+// Tipos de mano en los que se agrupan las decisiones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Categoria {
+    Duras,
+    Blandas,
+    Parejas,
+}
+
+impl Categoria {
+    pub const TODAS: [Categoria; 3] = [Categoria::Duras, Categoria::Blandas, Categoria::Parejas];
+
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            Categoria::Duras => "Totales duros",
+            Categoria::Blandas => "Totales blandos",
+            Categoria::Parejas => "Parejas",
+        }
+    }
+}
+
+// Una decisión del jugador comparada con la estrategia básica
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decision {
+    pub situacion: Situacion,
+    pub tomada: Accion,
+    pub correcta: Accion,
+    // Valor esperado perdido frente a la jugada correcta, en apuestas iniciales
+    pub coste: f64,
+}
+
+impl Decision {
+    pub fn es_error(&self) -> bool {
+        self.tomada != self.correcta
+    }
+
+    pub fn categoria(&self) -> Categoria {
+        if self.situacion.par.is_some() {
+            Categoria::Parejas
+        } else if self.situacion.blanda {
+            Categoria::Blandas
+        } else {
+            Categoria::Duras
+        }
+    }
+
+    pub fn descripcion(&self) -> String {
+        self.situacion.descripcion()
+    }
+}
+
+// Resumen de las decisiones de una categoría
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Resumen {
+    pub decisiones: usize,
+    pub errores: usize,
+    pub coste: f64,
+}
+
+impl Resumen {
+    pub fn tasa_error(&self) -> f64 {
+        if self.decisiones == 0 {
+            0.0
+        } else {
+            self.errores as f64 / self.decisiones as f64
+        }
+    }
+}
+
+// Decisiones tomadas durante la sesión
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistroDecisiones {
+    pub decisiones: Vec<Decision>,
+}
+
+impl RegistroDecisiones {
+    pub fn registrar(
+        &mut self,
+        situacion: Situacion,
+        opciones: Opciones,
+        tomada: Accion,
+        reglas: &ReglasMesa,
+    ) -> &Decision {
+        let mut correcta = accion_basica(situacion, reglas, opciones);
+        let mut coste = 0.0;
+        if tomada != correcta {
+            let mut evaluador = Evaluador::nuevo(situacion.banca, reglas);
+            coste = evaluador.ev(&situacion, correcta) - evaluador.ev(&situacion, tomada);
+            // En las jugadas muy ajustadas la tabla puede no coincidir con el shoe
+            // infinito: si la jugada tomada no pierde valor esperado no es un error
+            if coste <= 0.0 {
+                correcta = tomada;
+                coste = 0.0;
+            }
+        }
+
+        self.decisiones.push(Decision {
+            situacion,
+            tomada,
+            correcta,
+            coste,
+        });
+        self.decisiones.last().unwrap()
+    }
+
+    pub fn errores(&self) -> impl DoubleEndedIterator<Item = &Decision> {
+        self.decisiones
+            .iter()
+            .filter(|decision| decision.es_error())
+    }
+
+    pub fn resumen(&self, categoria: Option<Categoria>) -> Resumen {
+        self.decisiones
+            .iter()
+            .filter(|decision| categoria.is_none_or(|c| decision.categoria() == c))
+            .fold(Resumen::default(), |mut resumen, decision| {
+                resumen.decisiones += 1;
+                if decision.es_error() {
+                    resumen.errores += 1;
+                    resumen.coste += decision.coste;
+                }
+                resumen
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TODAS: Opciones = Opciones {
+        doblar: true,
+        dividir: true,
+        rendirse: false,
+    };
+
+    fn situacion(total: u8, blanda: bool, par: Option<u8>, banca: u8) -> Situacion {
+        Situacion {
+            total,
+            blanda,
+            par,
+            banca,
+        }
+    }
+
+    #[test]
+    fn test_registrar_decisiones() {
+        let reglas = ReglasMesa::default();
+        let mut registro = RegistroDecisiones::default();
+
+        let acierto = registro.registrar(
+            situacion(11, false, None, 6),
+            TODAS,
+            Accion::Doblar,
+            &reglas,
+        );
+        assert!(!acierto.es_error());
+        assert_eq!(acierto.coste, 0.0);
+
+        let error = registro.registrar(
+            situacion(16, false, Some(8), 10),
+            TODAS,
+            Accion::Plantarse,
+            &reglas,
+        );
+        assert!(error.es_error());
+        assert_eq!(error.correcta, Accion::Dividir);
+        assert!(error.coste > 0.0);
+        assert_eq!(error.descripcion(), "8-8 vs 10");
+
+        registro.registrar(
+            situacion(18, true, None, 9),
+            TODAS,
+            Accion::Plantarse,
+            &reglas,
+        );
+
+        assert_eq!(registro.errores().count(), 2);
+        let parejas = registro.resumen(Some(Categoria::Parejas));
+        assert_eq!(parejas.decisiones, 1);
+        assert_eq!(parejas.tasa_error(), 1.0);
+        assert_eq!(registro.resumen(Some(Categoria::Duras)).errores, 0);
+        assert_eq!(registro.resumen(Some(Categoria::Blandas)).errores, 1);
+
+        let total = registro.resumen(None);
+        assert_eq!(total.decisiones, 3);
+        assert_eq!(total.errores, 2);
+    }
+
+    #[test]
+    fn test_jugada_sin_coste_no_es_error() {
+        // Pedir con 13 blando contra un 5 vale algo más que doblar con un shoe infinito
+        let reglas = ReglasMesa::default();
+        let mut registro = RegistroDecisiones::default();
+        let decision =
+            registro.registrar(situacion(13, true, None, 5), TODAS, Accion::Pedir, &reglas);
+        assert!(!decision.es_error());
+        assert_eq!(decision.correcta, Accion::Pedir);
+
+        // Todo error tiene un coste
+        for tomada in [Accion::Plantarse, Accion::Doblar, Accion::Dividir] {
+            let decision =
+                registro.registrar(situacion(16, false, Some(8), 11), TODAS, tomada, &reglas);
+            assert_eq!(decision.es_error(), decision.coste > 0.0);
+        }
+        assert_eq!(registro.resumen(None).errores, 2);
+    }
+
+    #[test]
+    fn test_resumen_vacio() {
+        let registro = RegistroDecisiones::default();
+        assert_eq!(registro.resumen(None).tasa_error(), 0.0);
+    }
+}
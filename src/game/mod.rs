@@ -1,3 +1,18 @@
+pub mod chart;
+pub mod counting;
 pub mod deck;
+pub mod deviations;
+pub mod drill;
+pub mod ev;
+pub mod hand;
+pub mod history;
 pub mod logic;
+pub mod mistakes;
 pub mod player;
+pub mod profile;
+pub mod replay;
+pub mod rules;
+pub mod shoe;
+pub mod simulation;
+pub mod strategy;
+pub mod trends;
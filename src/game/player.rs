@@ -1,50 +1,92 @@
 use crate::game::deck::Carta;
+use crate::game::hand::Mano;
+use crate::game::shoe::Shoe;
+use serde::{Deserialize, Serialize};
 
+pub const SALDO_INICIAL: u32 = 1000;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Jugador {
-    pub mano: Vec<Carta>,
+    pub manos: Vec<Mano>,
+    pub mano_activa: usize,
     pub nombre: String,
-    pub puntos: u8,
     pub partidas_ganadas: u32,
+    pub saldo: u32,
+    pub seguro: u32,
 }
 
 impl Jugador {
     pub fn nuevo() -> Self {
         Jugador {
-            mano: Vec::new(),
+            manos: vec![Mano::nueva(0)],
+            mano_activa: 0,
             nombre: "Jugador".to_string(),
-            puntos: 0,
             partidas_ganadas: 0,
+            saldo: SALDO_INICIAL,
+            seguro: 0,
         }
     }
 
-    pub fn tomar_carta(&mut self, baraja: &mut Vec<Carta>) {
-        if let Some(carta) = baraja.pop() {
-            self.mano.push(carta);
-        }
+    // Mano que se está jugando en este momento
+    pub fn mano(&self) -> &Mano {
+        &self.manos[self.mano_activa]
     }
 
-    pub fn puntaje(&self) -> u8 {
-        let mut total = 0;
-        let mut ases = 0;
+    pub fn mano_mut(&mut self) -> &mut Mano {
+        &mut self.manos[self.mano_activa]
+    }
 
-        for carta in &self.mano {
-            total += carta.puntos();
-            if carta.valor == 1 {
-                ases += 1;
-            }
-        }
+    pub fn tomar_carta(&mut self, shoe: &mut Shoe) {
+        self.mano_mut().tomar_carta(shoe);
+    }
 
-        while total > 21 && ases > 0 {
-            total -= 10;
-            ases -= 1;
-        }
+    // Descarta todas las manos y deja una vacía con la apuesta indicada
+    pub fn limpiar_manos(&mut self, apuesta: u32) {
+        self.manos = vec![Mano::nueva(apuesta)];
+        self.mano_activa = 0;
+        self.seguro = 0;
+    }
+
+    // El turno acaba cuando todas las manos se han terminado de jugar
+    pub fn turno_terminado(&self) -> bool {
+        self.manos.iter().all(|mano| mano.terminada)
+    }
 
-        total
+    // La banca solo necesita robar si queda alguna mano que no esté decidida
+    pub fn necesita_banca(&self) -> bool {
+        self.manos
+            .iter()
+            .any(|mano| !mano.pasada() && !mano.es_blackjack() && !mano.rendida)
+    }
+
+    pub fn todas_pasadas(&self) -> bool {
+        self.manos.iter().all(|mano| mano.pasada())
+    }
+
+    // Suma de las apuestas de todas las manos en juego
+    pub fn apuesta_total(&self) -> u32 {
+        self.manos.iter().map(|mano| mano.apuesta).sum()
     }
 
     pub fn partida_ganada(&mut self) {
         self.partidas_ganadas += 1;
     }
+
+    // Comprueba un jugador leído de una partida guardada antes de jugar con él. El error
+    // no dice de quién es: la banca también se llama "Jugador"
+    pub fn validar(&self) -> Result<(), String> {
+        if self.manos.is_empty() {
+            return Err("no tiene ninguna mano".to_string());
+        }
+        if self.mano_activa >= self.manos.len() {
+            return Err("tiene como activa una mano que no existe".to_string());
+        }
+        let mut cartas = self.manos.iter().flat_map(|mano| &mano.cartas);
+        if !cartas.all(Carta::es_valida) {
+            return Err("tiene una carta que no existe".to_string());
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -56,15 +98,18 @@ mod tests {
     fn test_jugador_nuevo() {
         let jugador = Jugador::nuevo();
         assert_eq!(jugador.nombre, "Jugador");
-        assert_eq!(jugador.puntos, 0);
         assert_eq!(jugador.partidas_ganadas, 0);
-        assert!(jugador.mano.is_empty());
+        assert_eq!(jugador.manos.len(), 1);
+        assert_eq!(jugador.mano_activa, 0);
+        assert_eq!(jugador.mano().apuesta, 0);
+        assert_eq!(jugador.saldo, SALDO_INICIAL);
+        assert!(jugador.mano().cartas.is_empty());
     }
 
     #[test]
     fn test_tomar_carta() {
         let mut jugador = Jugador::nuevo();
-        let mut baraja = vec![
+        let mut shoe = Shoe::from(vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 10,
@@ -73,22 +118,22 @@ mod tests {
                 palo: Palo::Diamantes,
                 valor: 5,
             },
-        ];
+        ]);
 
-        jugador.tomar_carta(&mut baraja);
-        assert_eq!(jugador.mano.len(), 1);
-        assert_eq!(baraja.len(), 1);
-        assert_eq!(jugador.mano[0].valor, 5);
+        jugador.tomar_carta(&mut shoe);
+        assert_eq!(jugador.mano().cartas.len(), 1);
+        assert_eq!(shoe.restantes(), 1);
+        assert_eq!(jugador.mano().cartas[0].valor, 5);
 
-        jugador.tomar_carta(&mut baraja);
-        assert_eq!(jugador.mano.len(), 2);
-        assert!(baraja.is_empty());
+        jugador.tomar_carta(&mut shoe);
+        assert_eq!(jugador.mano().cartas.len(), 2);
+        assert_eq!(shoe.restantes(), 0);
     }
 
     #[test]
     fn test_puntaje_simple() {
         let mut jugador = Jugador::nuevo();
-        jugador.mano = vec![
+        jugador.mano_mut().cartas = vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 10,
@@ -99,13 +144,13 @@ mod tests {
             },
         ];
 
-        assert_eq!(jugador.puntaje(), 15);
+        assert_eq!(jugador.mano().puntaje(), 15);
     }
 
     #[test]
     fn test_puntaje_con_as() {
         let mut jugador = Jugador::nuevo();
-        jugador.mano = vec![
+        jugador.mano_mut().cartas = vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 1,
@@ -116,13 +161,13 @@ mod tests {
             }, // 10
         ];
 
-        assert_eq!(jugador.puntaje(), 21);
+        assert_eq!(jugador.mano().puntaje(), 21);
     }
 
     #[test]
     fn test_puntaje_con_multiples_ases() {
         let mut jugador = Jugador::nuevo();
-        jugador.mano = vec![
+        jugador.mano_mut().cartas = vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 1,
@@ -137,13 +182,13 @@ mod tests {
             }, // As
         ];
 
-        assert_eq!(jugador.puntaje(), 13); // 11 + 1 + 1
+        assert_eq!(jugador.mano().puntaje(), 13); // 11 + 1 + 1
     }
 
     #[test]
     fn test_puntaje_con_blackjack() {
         let mut jugador = Jugador::nuevo();
-        jugador.mano = vec![
+        jugador.mano_mut().cartas = vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 1,
@@ -154,7 +199,7 @@ mod tests {
             }, // Rey (10 puntos)
         ];
 
-        assert_eq!(jugador.puntaje(), 21);
+        assert_eq!(jugador.mano().puntaje(), 21);
     }
 
     #[test]
@@ -172,7 +217,7 @@ mod tests {
     #[test]
     fn test_puntaje_as_que_cambia() {
         let mut jugador = Jugador::nuevo();
-        jugador.mano = vec![
+        jugador.mano_mut().cartas = vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 1,
@@ -187,13 +232,13 @@ mod tests {
             }, // 10
         ];
 
-        assert_eq!(jugador.puntaje(), 16); // As vale 1
+        assert_eq!(jugador.mano().puntaje(), 16); // As vale 1
     }
 
     #[test]
     fn test_puntaje_con_figuras() {
         let mut jugador = Jugador::nuevo();
-        jugador.mano = vec![
+        jugador.mano_mut().cartas = vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 11,
@@ -204,13 +249,13 @@ mod tests {
             }, // Q
         ];
 
-        assert_eq!(jugador.puntaje(), 20);
+        assert_eq!(jugador.mano().puntaje(), 20);
     }
 
     #[test]
     fn test_puntaje_con_mas_de_21() {
         let mut jugador = Jugador::nuevo();
-        jugador.mano = vec![
+        jugador.mano_mut().cartas = vec![
             Carta {
                 palo: Palo::Corazones,
                 valor: 10,
@@ -225,6 +270,37 @@ mod tests {
             },
         ];
 
-        assert_eq!(jugador.puntaje(), 25);
+        assert_eq!(jugador.mano().puntaje(), 25);
+    }
+
+    #[test]
+    fn test_limpiar_manos() {
+        let mut jugador = Jugador::nuevo();
+        jugador.manos.push(Mano::nueva(1));
+        jugador.mano_activa = 1;
+
+        jugador.limpiar_manos(3);
+        assert_eq!(jugador.manos.len(), 1);
+        assert_eq!(jugador.mano_activa, 0);
+        assert_eq!(jugador.mano().apuesta, 3);
+    }
+
+    #[test]
+    fn test_todas_pasadas() {
+        let mut jugador = Jugador::nuevo();
+        jugador.manos = vec![Mano::nueva(1), Mano::nueva(1)];
+        jugador.manos[0].puntos = 24;
+        jugador.manos[1].puntos = 18;
+        assert!(!jugador.todas_pasadas());
+
+        jugador.manos[1].puntos = 22;
+        assert!(jugador.todas_pasadas());
+    }
+
+    #[test]
+    fn test_apuesta_total() {
+        let mut jugador = Jugador::nuevo();
+        jugador.manos = vec![Mano::nueva(10), Mano::nueva(20)];
+        assert_eq!(jugador.apuesta_total(), 30);
     }
 }
@@ -0,0 +1,213 @@
+use crate::game::history::RondaJugada;
+use crate::game::logic::Resultado;
+use serde::{Deserialize, Serialize};
+
+// Estadísticas de toda la vida de un perfil
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EstadisticasPerfil {
+    pub rondas: u32,
+    pub manos: u32,
+    pub ganadas: u32,
+    pub perdidas: u32,
+    pub empates: u32,
+    pub blackjacks: u32,
+    pub pasadas: u32,
+    pub dobladas_ganadas: u32,
+    // Ganancia o pérdida acumulada de todas las sesiones
+    pub neto: i64,
+    // Rondas seguidas con ganancia (positiva) o con pérdida (negativa)
+    pub racha: i32,
+    pub mejor_racha: u32,
+    pub peor_racha: u32,
+}
+
+impl EstadisticasPerfil {
+    // Suma una ronda terminada. Las rondas sin ganancia ni pérdida no cortan la racha
+    pub fn anotar(&mut self, ronda: &RondaJugada) {
+        self.rondas += 1;
+        self.neto += ronda.neto;
+
+        for mano in &ronda.manos {
+            self.manos += 1;
+            match mano.resultado {
+                Resultado::Blackjack
+                | Resultado::Gana
+                | Resultado::BancaSePasa
+                | Resultado::DineroPar => {
+                    self.ganadas += 1;
+                    if mano.doblada {
+                        self.dobladas_ganadas += 1;
+                    }
+                }
+                Resultado::Empate => self.empates += 1,
+                Resultado::Pierde
+                | Resultado::SePasa
+                | Resultado::BlackjackBanca
+                | Resultado::Rendicion => self.perdidas += 1,
+            }
+            match mano.resultado {
+                Resultado::Blackjack | Resultado::DineroPar => self.blackjacks += 1,
+                Resultado::SePasa => self.pasadas += 1,
+                _ => {}
+            }
+        }
+
+        if ronda.neto > 0 {
+            self.racha = self.racha.max(0) + 1;
+            self.mejor_racha = self.mejor_racha.max(self.racha as u32);
+        } else if ronda.neto < 0 {
+            self.racha = self.racha.min(0) - 1;
+            self.peor_racha = self.peor_racha.max(self.racha.unsigned_abs());
+        }
+    }
+
+    pub fn tasa_victorias(&self) -> f64 {
+        if self.manos == 0 {
+            0.0
+        } else {
+            self.ganadas as f64 / self.manos as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerfilJugador {
+    pub nombre: String,
+    #[serde(default)]
+    pub estadisticas: EstadisticasPerfil,
+}
+
+// Perfiles guardados en el directorio de datos, p. ej. ~/.local/share/ratjack/perfiles.toml
+//
+//     ultimo = "Ana"
+//
+//     [[perfil]]
+//     nombre = "Ana"
+//
+//     [perfil.estadisticas]
+//     rondas = 120
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Perfiles {
+    // Último perfil con el que se jugó, que se propone al empezar
+    pub ultimo: Option<String>,
+    #[serde(default, rename = "perfil")]
+    pub perfiles: Vec<PerfilJugador>,
+}
+
+impl Perfiles {
+    pub fn desde_toml(texto: &str) -> Result<Self, String> {
+        toml::from_str(texto).map_err(|e| e.to_string())
+    }
+
+    pub fn a_toml(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| e.to_string())
+    }
+
+    pub fn buscar(&self, nombre: &str) -> Option<usize> {
+        self.perfiles
+            .iter()
+            .position(|perfil| perfil.nombre == nombre)
+    }
+
+    // Añade un perfil nuevo y devuelve su posición. Los nombres no se repiten
+    pub fn crear(&mut self, nombre: &str) -> Result<usize, String> {
+        let nombre = nombre.trim();
+        if nombre.is_empty() {
+            return Err("El nombre no puede estar vacío".to_string());
+        }
+        if self.buscar(nombre).is_some() {
+            return Err(format!("Ya existe un perfil llamado {}", nombre));
+        }
+        self.perfiles.push(PerfilJugador {
+            nombre: nombre.to_string(),
+            estadisticas: EstadisticasPerfil::default(),
+        });
+        Ok(self.perfiles.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::history::ManoJugada;
+    use crate::game::rules::ReglasMesa;
+
+    fn ronda(manos: &[(Resultado, bool)], neto: i64) -> RondaJugada {
+        RondaJugada {
+            ronda: 1,
+            fecha: 0,
+            semilla: 0,
+            reglas: ReglasMesa::default(),
+            posicion: 0,
+            apuesta: 10,
+            seguro: 0,
+            jugador: Vec::new(),
+            banca: Vec::new(),
+            acciones: Vec::new(),
+            robadas_banca: Vec::new(),
+            manos: manos
+                .iter()
+                .map(|&(resultado, doblada)| ManoJugada {
+                    cartas: Vec::new(),
+                    apuesta: 10,
+                    doblada,
+                    resultado,
+                })
+                .collect(),
+            saldo_inicial: 1000,
+            saldo_final: (1000 + neto) as u32,
+            neto,
+        }
+    }
+
+    #[test]
+    fn test_anotar_rondas() {
+        let mut estadisticas = EstadisticasPerfil::default();
+        estadisticas.anotar(&ronda(&[(Resultado::Blackjack, false)], 15));
+        estadisticas.anotar(&ronda(&[(Resultado::Gana, true)], 20));
+        estadisticas.anotar(&ronda(&[(Resultado::Empate, false)], 0));
+        estadisticas.anotar(&ronda(
+            &[(Resultado::SePasa, false), (Resultado::Pierde, false)],
+            -20,
+        ));
+
+        assert_eq!(estadisticas.rondas, 4);
+        assert_eq!(estadisticas.manos, 5);
+        assert_eq!(
+            (
+                estadisticas.ganadas,
+                estadisticas.perdidas,
+                estadisticas.empates
+            ),
+            (2, 2, 1)
+        );
+        assert_eq!(estadisticas.blackjacks, 1);
+        assert_eq!(estadisticas.pasadas, 1);
+        assert_eq!(estadisticas.dobladas_ganadas, 1);
+        assert_eq!(estadisticas.neto, 15);
+        assert_eq!(estadisticas.tasa_victorias(), 0.4);
+
+        // El empate no corta la racha de dos victorias
+        assert_eq!(estadisticas.mejor_racha, 2);
+        assert_eq!(estadisticas.racha, -1);
+        estadisticas.anotar(&ronda(&[(Resultado::Rendicion, false)], -5));
+        assert_eq!(estadisticas.peor_racha, 2);
+    }
+
+    #[test]
+    fn test_perfiles_toml() {
+        let mut perfiles = Perfiles::default();
+        assert_eq!(perfiles.crear(" Ana "), Ok(0));
+        assert!(perfiles.crear("Ana").is_err());
+        assert!(perfiles.crear("  ").is_err());
+        perfiles.perfiles[0].estadisticas.rondas = 3;
+        perfiles.ultimo = Some("Ana".to_string());
+
+        let texto = perfiles.a_toml().unwrap();
+        assert_eq!(Perfiles::desde_toml(&texto).unwrap(), perfiles);
+        assert_eq!(Perfiles::desde_toml("").unwrap(), Perfiles::default());
+        assert!(Perfiles::desde_toml("jugadores = 1").is_err());
+    }
+}
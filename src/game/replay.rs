@@ -0,0 +1,426 @@
+use crate::game::deck::Carta;
+use crate::game::history::RondaJugada;
+use crate::game::logic::{
+    avanzar_mano, dividir, doblar, jugar_turno, rendirse, repartir_cartas, tomar_seguro,
+    Liquidacion,
+};
+use crate::game::mistakes::{Decision, RegistroDecisiones};
+use crate::game::player::Jugador;
+use crate::game::shoe::Shoe;
+use crate::game::strategy::{decision_actual, Accion};
+
+// Estado de la mesa en un momento de una ronda repetida
+#[derive(Clone)]
+pub struct Fotograma {
+    pub jugador: Jugador,
+    pub banca: Jugador,
+    pub banca_descubierta: bool,
+    pub descripcion: String,
+    // Jugada que lleva a este momento comparada con la estrategia básica
+    pub decision: Option<Decision>,
+}
+
+impl Fotograma {
+    fn nuevo(descripcion: String, jugador: &Jugador, banca: &Jugador, descubierta: bool) -> Self {
+        Fotograma {
+            jugador: jugador.clone(),
+            banca: banca.clone(),
+            banca_descubierta: descubierta,
+            descripcion,
+            decision: None,
+        }
+    }
+
+    pub fn es_error(&self) -> bool {
+        self.decision
+            .as_ref()
+            .is_some_and(|decision| decision.es_error())
+    }
+}
+
+// Vuelve a jugar una ronda del historial con sus cartas y jugadas, un fotograma por paso
+pub fn reconstruir(ronda: &RondaJugada) -> Vec<Fotograma> {
+    let reglas = &ronda.reglas;
+
+    // Cartas en el orden en que salieron del shoe: el reparto alterna jugador y banca
+    let reparto = [
+        ronda.jugador.first(),
+        ronda.banca.first(),
+        ronda.jugador.get(1),
+        ronda.banca.get(1),
+    ];
+    let mut cartas: Vec<Carta> = reparto.into_iter().flatten().copied().collect();
+    cartas.extend(ronda.acciones.iter().flat_map(|paso| &paso.cartas));
+    cartas.extend(&ronda.robadas_banca);
+    // El shoe roba desde el final
+    cartas.reverse();
+    let mut shoe = Shoe::from(cartas);
+
+    let mut jugador = Jugador::nuevo();
+    let mut banca = Jugador::nuevo();
+    jugador.saldo = ronda.saldo_inicial.saturating_sub(ronda.apuesta);
+    jugador.limpiar_manos(ronda.apuesta);
+    repartir_cartas(&mut jugador, &mut banca, &mut shoe, reglas);
+
+    let mut fotogramas = vec![Fotograma::nuevo(
+        format!("Reparto con apuesta de {}", ronda.apuesta),
+        &jugador,
+        &banca,
+        false,
+    )];
+    if ronda.seguro > 0 && tomar_seguro(&mut jugador) {
+        fotogramas.push(Fotograma::nuevo(
+            format!("Seguro de {}", ronda.seguro),
+            &jugador,
+            &banca,
+            false,
+        ));
+    }
+
+    let mut registro = RegistroDecisiones::default();
+    for paso in &ronda.acciones {
+        // La jugada se compara en la mano en la que se hizo
+        jugador.mano_activa = paso.mano.min(jugador.manos.len() - 1);
+        let decision =
+            decision_actual(&jugador, &banca, reglas, false).map(|(situacion, opciones)| {
+                registro
+                    .registrar(situacion, opciones, paso.accion, reglas)
+                    .clone()
+            });
+        match paso.accion {
+            Accion::Pedir => {
+                jugar_turno(&mut jugador, &mut shoe, true);
+                if jugador.mano().pasada() {
+                    avanzar_mano(&mut jugador, &mut shoe, reglas);
+                }
+            }
+            Accion::Plantarse => {
+                avanzar_mano(&mut jugador, &mut shoe, reglas);
+            }
+            Accion::Doblar => {
+                doblar(&mut jugador, &mut shoe, reglas);
+            }
+            Accion::Dividir => {
+                dividir(&mut jugador, &mut shoe, reglas);
+            }
+            Accion::Rendirse => {
+                // La rendición temprana se juega antes del peek
+                if !rendirse(&mut jugador, reglas, true) {
+                    rendirse(&mut jugador, reglas, false);
+                }
+            }
+        }
+
+        let mut descripcion = paso.accion.nombre().to_string();
+        if ronda.manos.len() > 1 {
+            descripcion = format!("Mano {}: {}", paso.mano + 1, descripcion);
+        }
+        if !paso.cartas.is_empty() {
+            descripcion = format!("{} → {}", descripcion, texto_cartas(&paso.cartas));
+        }
+        let mut fotograma = Fotograma::nuevo(descripcion, &jugador, &banca, false);
+        fotograma.decision = decision;
+        fotogramas.push(fotograma);
+    }
+
+    for carta in &ronda.robadas_banca {
+        banca.tomar_carta(&mut shoe);
+        fotogramas.push(Fotograma::nuevo(
+            format!("La banca roba {}", texto_cartas(&[*carta])),
+            &jugador,
+            &banca,
+            true,
+        ));
+    }
+
+    // El saldo final ya incluye el seguro y todos los pagos
+    jugador.saldo = ronda.saldo_final;
+    jugador.seguro = 0;
+    let liquidacion = Liquidacion {
+        resultados: ronda.manos.iter().map(|mano| mano.resultado).collect(),
+        seguro: None,
+    };
+    fotogramas.push(Fotograma::nuevo(
+        format!("{} Saldo {:+}", liquidacion.mensaje(), ronda.neto),
+        &jugador,
+        &banca,
+        true,
+    ));
+    fotogramas
+}
+
+fn texto_cartas(cartas: &[Carta]) -> String {
+    cartas
+        .iter()
+        .map(|carta| format!("{}{}", carta.valor_str(), carta.simbolo()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Visor de un historial: las rondas, las que ya se han reconstruido y la posición actual.
+// Cada ronda se reconstruye la primera vez que se visita, así que abrir el visor no
+// depende de lo largo que sea el historial
+pub struct Repeticion {
+    rondas: Vec<RondaJugada>,
+    fotogramas: Vec<Option<Vec<Fotograma>>>,
+    pub ronda: usize,
+    pub paso: usize,
+}
+
+impl Repeticion {
+    // Empieza en la última ronda jugada. Devuelve None si no hay rondas
+    pub fn nueva(rondas: Vec<RondaJugada>) -> Option<Self> {
+        if rondas.is_empty() {
+            return None;
+        }
+        let mut repeticion = Repeticion {
+            ronda: rondas.len() - 1,
+            paso: 0,
+            fotogramas: vec![None; rondas.len()],
+            rondas,
+        };
+        repeticion.reconstruir_actual();
+        Some(repeticion)
+    }
+
+    fn reconstruir_actual(&mut self) {
+        let ronda = &self.rondas[self.ronda];
+        self.fotogramas[self.ronda].get_or_insert_with(|| reconstruir(ronda));
+    }
+
+    // Fotogramas de la ronda actual, que siempre está reconstruida
+    fn fotogramas_actuales(&self) -> &[Fotograma] {
+        self.fotogramas[self.ronda]
+            .as_deref()
+            .expect("la ronda actual se reconstruye al visitarla")
+    }
+
+    pub fn ronda_actual(&self) -> &RondaJugada {
+        &self.rondas[self.ronda]
+    }
+
+    pub fn fotograma(&self) -> &Fotograma {
+        &self.fotogramas_actuales()[self.paso]
+    }
+
+    pub fn total_rondas(&self) -> usize {
+        self.rondas.len()
+    }
+
+    pub fn total_pasos(&self) -> usize {
+        self.fotogramas_actuales().len()
+    }
+
+    pub fn errores(&self) -> usize {
+        self.fotogramas_actuales()
+            .iter()
+            .filter(|fotograma| fotograma.es_error())
+            .count()
+    }
+
+    // Un paso adelante, pasando al principio de la ronda siguiente al acabar
+    pub fn avanzar(&mut self) -> bool {
+        if self.paso + 1 < self.total_pasos() {
+            self.paso += 1;
+        } else if self.ronda + 1 < self.rondas.len() {
+            self.ronda += 1;
+            self.paso = 0;
+            self.reconstruir_actual();
+        } else {
+            return false;
+        }
+        true
+    }
+
+    // Un paso atrás, pasando al final de la ronda anterior
+    pub fn retroceder(&mut self) -> bool {
+        if self.paso > 0 {
+            self.paso -= 1;
+        } else if self.ronda > 0 {
+            self.ronda -= 1;
+            self.reconstruir_actual();
+            self.paso = self.total_pasos() - 1;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    pub fn ir_a_ronda(&mut self, indice: usize) -> bool {
+        if indice >= self.rondas.len() {
+            return false;
+        }
+        self.ronda = indice;
+        self.paso = 0;
+        self.reconstruir_actual();
+        true
+    }
+
+    // Salta a la siguiente jugada que no sigue la estrategia básica
+    pub fn siguiente_error(&mut self) -> bool {
+        let (ronda, paso) = (self.ronda, self.paso);
+        while self.avanzar() {
+            if self.fotograma().es_error() {
+                return true;
+            }
+        }
+        self.ronda = ronda;
+        self.paso = paso;
+        false
+    }
+
+    // Posición en todo el historial, de 0 a 1, para la barra de tiempo. Cada ronda
+    // ocupa lo mismo, ya que las que no se han visitado no se sabe cuántos pasos tienen
+    pub fn progreso(&self) -> f64 {
+        let dentro = self.paso as f64 / (self.total_pasos() - 1).max(1) as f64;
+        (self.ronda as f64 + dentro) / self.rondas.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::deck::Palo;
+    use crate::game::logic::{apostar, determinar_ganador, jugar_banca};
+    use crate::game::player::SALDO_INICIAL;
+    use crate::game::rules::ReglasMesa;
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: Palo::Picas,
+        }
+    }
+
+    // Juega una ronda como la interfaz, anotando cada jugada en el historial.
+    // Las cartas se dan en el orden en que salen del shoe
+    fn jugar(cartas: &[u8], acciones: &[Accion]) -> RondaJugada {
+        let reglas = ReglasMesa::default();
+        let mut shoe = Shoe::from(
+            cartas
+                .iter()
+                .rev()
+                .map(|&valor| carta(valor))
+                .collect::<Vec<_>>(),
+        );
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+
+        assert!(apostar(&mut jugador, 100, &reglas));
+        repartir_cartas(&mut jugador, &mut banca, &mut shoe, &reglas);
+        let mut ronda = RondaJugada::empezar(1, 0, &reglas, 0, SALDO_INICIAL, &jugador, &banca);
+
+        for &accion in acciones {
+            let antes = shoe.repartidas().len();
+            let mano = jugador.mano_activa;
+            match accion {
+                Accion::Pedir => {
+                    jugar_turno(&mut jugador, &mut shoe, true);
+                    if jugador.mano().pasada() {
+                        avanzar_mano(&mut jugador, &mut shoe, &reglas);
+                    }
+                }
+                Accion::Plantarse => {
+                    avanzar_mano(&mut jugador, &mut shoe, &reglas);
+                }
+                Accion::Doblar => assert!(doblar(&mut jugador, &mut shoe, &reglas)),
+                Accion::Dividir => assert!(dividir(&mut jugador, &mut shoe, &reglas)),
+                Accion::Rendirse => assert!(rendirse(&mut jugador, &reglas, false)),
+            }
+            ronda.anotar(mano, accion, &shoe.repartidas()[antes..]);
+        }
+
+        if !jugador.todas_pasadas() {
+            jugar_banca(&jugador, &mut banca, &mut shoe, &reglas);
+        }
+        let liquidacion = determinar_ganador(&mut jugador, &mut banca, &reglas);
+        ronda.terminar(&jugador, &banca, &liquidacion);
+        ronda
+    }
+
+    #[test]
+    fn test_reconstruir_division() {
+        // 8-8 contra 6-10: divide, recibe 3 y se planta, recibe 10 y se planta; la banca roba un 9
+        let ronda = jugar(
+            &[8, 6, 8, 10, 3, 10, 9],
+            &[Accion::Dividir, Accion::Plantarse, Accion::Plantarse],
+        );
+        let fotogramas = reconstruir(&ronda);
+
+        // Reparto, tres jugadas, un robo de la banca y el resultado
+        assert_eq!(fotogramas.len(), 6);
+        assert!(!fotogramas[0].banca_descubierta);
+        assert_eq!(fotogramas[1].jugador.manos.len(), 2);
+        assert_eq!(fotogramas[1].descripcion, "Mano 1: Dividir → 3♤");
+        assert!(!fotogramas[1].es_error());
+
+        let ultimo = fotogramas.last().unwrap();
+        let manos: Vec<_> = ultimo
+            .jugador
+            .manos
+            .iter()
+            .map(|mano| mano.cartas.clone())
+            .collect();
+        let registradas: Vec<_> = ronda.manos.iter().map(|mano| mano.cartas.clone()).collect();
+        assert_eq!(manos, registradas);
+        assert_eq!(ultimo.banca.mano().cartas, [carta(6), carta(10), carta(9)]);
+        assert_eq!(ultimo.jugador.saldo, SALDO_INICIAL + 200);
+        assert!(ultimo.banca_descubierta);
+    }
+
+    #[test]
+    fn test_decisiones_en_su_mano() {
+        // 8-8 contra 6-10: divide, se planta con 11 (debía doblar) y con 18
+        let ronda = jugar(
+            &[8, 6, 8, 10, 3, 10, 9],
+            &[Accion::Dividir, Accion::Plantarse, Accion::Plantarse],
+        );
+        let fotogramas = reconstruir(&ronda);
+        let decision = |paso: usize| fotogramas[paso].decision.as_ref().unwrap();
+        assert_eq!(decision(2).situacion.total, 11);
+        assert_eq!(decision(2).correcta, Accion::Doblar);
+        assert_eq!(decision(3).situacion.total, 18);
+        assert!(!decision(3).es_error());
+    }
+
+    #[test]
+    fn test_navegar_y_errores() {
+        // 10-10 contra 6-10: pedir con 20 es un error
+        let error = jugar(&[10, 6, 10, 10, 1, 5], &[Accion::Pedir, Accion::Plantarse]);
+        let bien = jugar(&[10, 6, 7, 10, 9], &[Accion::Plantarse]);
+        assert_eq!(
+            reconstruir(&error)[1].decision.as_ref().unwrap().correcta,
+            Accion::Plantarse
+        );
+
+        let mut repeticion = Repeticion::nueva(vec![bien.clone(), error, bien]).unwrap();
+        assert_eq!(repeticion.ronda, 2);
+        // Solo se ha reconstruido la ronda que se está viendo
+        let reconstruidas = |repeticion: &Repeticion| {
+            repeticion
+                .fotogramas
+                .iter()
+                .filter(|fotogramas| fotogramas.is_some())
+                .count()
+        };
+        assert_eq!(reconstruidas(&repeticion), 1);
+        assert!(repeticion.retroceder());
+        assert_eq!(repeticion.ronda, 1);
+        assert_eq!(repeticion.paso, repeticion.total_pasos() - 1);
+        assert_eq!(reconstruidas(&repeticion), 2);
+
+        assert!(repeticion.ir_a_ronda(0));
+        assert_eq!(repeticion.progreso(), 0.0);
+        assert!(repeticion.siguiente_error());
+        assert_eq!((repeticion.ronda, repeticion.paso), (1, 1));
+        assert_eq!(repeticion.errores(), 1);
+        assert!(!repeticion.siguiente_error());
+        assert_eq!((repeticion.ronda, repeticion.paso), (1, 1));
+
+        assert!(!repeticion.ir_a_ronda(3));
+        assert!(repeticion.ir_a_ronda(2));
+        while repeticion.avanzar() {}
+        assert_eq!(repeticion.progreso(), 1.0);
+        assert!(Repeticion::nueva(Vec::new()).is_none());
+    }
+}
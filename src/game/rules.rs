@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+// Restricciones sobre las manos que se pueden doblar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReglaDoblar {
+    Cualquiera,
+    NueveAOnce,
+    DiezAOnce,
+}
+
+// Pago de un blackjack natural
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PagoBlackjack {
+    #[serde(rename = "3:2")]
+    TresADos,
+    #[serde(rename = "6:5")]
+    SeisACinco,
+}
+
+impl PagoBlackjack {
+    // Ganancia neta de un blackjack con la apuesta indicada (redondeada hacia abajo)
+    pub fn ganancia(&self, apuesta: u32) -> u32 {
+        match self {
+            PagoBlackjack::TresADos => apuesta * 3 / 2,
+            PagoBlackjack::SeisACinco => apuesta * 6 / 5,
+        }
+    }
+}
+
+// Cuándo recibe la banca su segunda carta y qué se pierde ante un blackjack de la banca
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReglaPeek {
+    // Carta oculta americana: la banca revisa si tiene blackjack con un as o un diez visible
+    Americana,
+    // Sin carta oculta (ENHC): ante un blackjack se pierden también dobles y divisiones
+    Europea,
+    // Sin carta oculta, pero solo se pierde la apuesta original (OBO)
+    EuropeaApuestaOriginal,
+}
+
+impl ReglaPeek {
+    pub fn sin_carta_oculta(&self) -> bool {
+        !matches!(self, ReglaPeek::Americana)
+    }
+}
+
+// Cuándo se permite abandonar la mano recuperando media apuesta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rendicion {
+    No,
+    // Tras comprobar la banca si tiene blackjack
+    Tardia,
+    // Antes de comprobar la banca si tiene blackjack
+    Temprana,
+}
+
+// La banca se planta (S17) o pide (H17) con 17 blando
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReglaBanca {
+    S17,
+    H17,
+}
+
+// Cómo vuelven al shoe las cartas jugadas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TipoShoe {
+    // Shoe clásico: se baraja todo al llegar a la carta de corte
+    CartaCorte,
+    // Barajadora continua (CSM): los descartes vuelven al shoe tras cada ronda
+    Continuo,
+}
+
+// Reglas de la mesa que condicionan las acciones del jugador.
+// Los campos que falten al leer un perfil toman el valor por defecto
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReglasMesa {
+    pub nombre: String,
+    pub mazos: u8,
+    // Fracción del shoe repartida antes de la carta de corte
+    pub penetracion: f32,
+    pub tipo_shoe: TipoShoe,
+    pub doblar: ReglaDoblar,
+    pub doblar_tras_dividir: bool,
+    pub max_manos: usize,
+    pub redividir_ases: bool,
+    pub pago_blackjack: PagoBlackjack,
+    pub apuesta_minima: u32,
+    pub apuesta_maxima: u32,
+    pub peek: ReglaPeek,
+    pub seguro: bool,
+    pub rendicion: Rendicion,
+    pub banca: ReglaBanca,
+}
+
+impl Default for ReglasMesa {
+    fn default() -> Self {
+        PerfilMesa::VegasStrip.reglas()
+    }
+}
+
+impl ReglasMesa {
+    // Comprueba que las reglas leídas de un perfil tienen sentido
+    pub fn validar(&self) -> Result<(), String> {
+        if self.mazos == 0 {
+            return Err("La mesa necesita al menos un mazo".to_string());
+        }
+        if !(self.penetracion > 0.0 && self.penetracion <= 1.0) {
+            return Err("La penetración debe estar entre 0 y 1".to_string());
+        }
+        if self.max_manos == 0 {
+            return Err("El máximo de manos debe ser al menos 1".to_string());
+        }
+        if self.apuesta_minima == 0 || self.apuesta_minima > self.apuesta_maxima {
+            return Err("Los límites de apuesta no son válidos".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Perfiles de mesa predefinidos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PerfilMesa {
+    VegasStrip,
+    AtlanticCity,
+    Downtown,
+    Europea,
+}
+
+impl PerfilMesa {
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            PerfilMesa::VegasStrip => "Vegas Strip",
+            PerfilMesa::AtlanticCity => "Atlantic City",
+            PerfilMesa::Downtown => "Downtown (un mazo)",
+            PerfilMesa::Europea => "Europea",
+        }
+    }
+
+    pub fn reglas(&self) -> ReglasMesa {
+        let vegas = ReglasMesa {
+            nombre: self.nombre().to_string(),
+            mazos: 6,
+            penetracion: 0.75,
+            tipo_shoe: TipoShoe::CartaCorte,
+            doblar: ReglaDoblar::Cualquiera,
+            doblar_tras_dividir: true,
+            max_manos: 4,
+            redividir_ases: false,
+            pago_blackjack: PagoBlackjack::TresADos,
+            apuesta_minima: 10,
+            apuesta_maxima: 500,
+            peek: ReglaPeek::Americana,
+            seguro: true,
+            rendicion: Rendicion::Tardia,
+            banca: ReglaBanca::S17,
+        };
+
+        match self {
+            PerfilMesa::VegasStrip => vegas,
+            PerfilMesa::AtlanticCity => ReglasMesa {
+                mazos: 8,
+                redividir_ases: false,
+                apuesta_minima: 15,
+                apuesta_maxima: 1000,
+                ..vegas
+            },
+            PerfilMesa::Downtown => ReglasMesa {
+                mazos: 1,
+                penetracion: 0.6,
+                doblar_tras_dividir: false,
+                apuesta_minima: 5,
+                apuesta_maxima: 200,
+                rendicion: Rendicion::No,
+                banca: ReglaBanca::H17,
+                ..vegas
+            },
+            PerfilMesa::Europea => ReglasMesa {
+                doblar: ReglaDoblar::NueveAOnce,
+                max_manos: 3,
+                peek: ReglaPeek::Europea,
+                rendicion: Rendicion::No,
+                ..vegas
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pago_blackjack() {
+        assert_eq!(PagoBlackjack::TresADos.ganancia(10), 15);
+        assert_eq!(PagoBlackjack::TresADos.ganancia(25), 37);
+        assert_eq!(PagoBlackjack::SeisACinco.ganancia(10), 12);
+        assert_eq!(PagoBlackjack::SeisACinco.ganancia(25), 30);
+    }
+
+    #[test]
+    fn test_perfiles_predefinidos() {
+        assert_eq!(ReglasMesa::default(), PerfilMesa::VegasStrip.reglas());
+        assert_eq!(PerfilMesa::AtlanticCity.reglas().mazos, 8);
+
+        let downtown = PerfilMesa::Downtown.reglas();
+        assert_eq!(downtown.mazos, 1);
+        assert_eq!(downtown.banca, ReglaBanca::H17);
+        assert_eq!(downtown.nombre, "Downtown (un mazo)");
+
+        let europea = PerfilMesa::Europea.reglas();
+        assert!(europea.peek.sin_carta_oculta());
+        assert_eq!(europea.rendicion, Rendicion::No);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::game::counting::{cuenta_corriente, SistemaConteo};
+use crate::game::deck::{cartas_mazo, Carta};
+use crate::game::rules::TipoShoe;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+// Zapato con varios mazos y carta de corte. Las cartas se roban desde el final
+// Se guarda entero, con el estado del generador, para seguir la partida otro día
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shoe {
+    cartas: Vec<Carta>,
+    descartes: Vec<Carta>,
+    // Cartas repartidas desde el último barajado, en orden, para el historial y el EV.
+    // Con CSM, solo las de la ronda en curso
+    repartidas: Vec<Carta>,
+    // Las mismas cartas contadas por puntos, del 2 al as, para llevar la cuenta sin
+    // recorrerlas. Se rehace al cargar una partida
+    #[serde(skip)]
+    vistas: [u32; 10],
+    total: usize,
+    // Fracción del shoe que se reparte antes de llegar a la carta de corte
+    penetracion: f32,
+    tipo: TipoShoe,
+    // Generador propio para que una semilla reproduzca el mismo orden de cartas
+    rng: ChaCha8Rng,
+    semilla: u64,
+}
+
+impl Shoe {
+    pub fn nuevo(mazos: u8, penetracion: f32, tipo: TipoShoe) -> Self {
+        Shoe::con_semilla(mazos, penetracion, tipo, nueva_semilla())
+    }
+
+    // Shoe cuyos barajados dependen solo de la semilla, igual en cualquier máquina
+    pub fn con_semilla(mazos: u8, penetracion: f32, tipo: TipoShoe, semilla: u64) -> Self {
+        let mut shoe = Shoe::from(
+            (0..mazos)
+                .flat_map(|_| cartas_mazo())
+                .collect::<Vec<Carta>>(),
+        );
+        shoe.penetracion = penetracion;
+        shoe.tipo = tipo;
+        shoe.rng = ChaCha8Rng::seed_from_u64(semilla);
+        shoe.semilla = semilla;
+        shoe.barajar();
+        shoe
+    }
+
+    // Si el shoe se agota a mitad de ronda, se rebarajan los descartes
+    pub fn robar(&mut self) -> Option<Carta> {
+        if self.cartas.is_empty() {
+            self.barajar();
+        }
+        let carta = self.cartas.pop()?;
+        self.repartidas.push(carta);
+        self.vistas[carta.puntos() as usize - 2] += 1;
+        Some(carta)
+    }
+
+    // Las cartas jugadas van a la bandeja de descartes
+    pub fn descartar<I: IntoIterator<Item = Carta>>(&mut self, cartas: I) {
+        self.descartes.extend(cartas);
+    }
+
+    // Devuelve los descartes al shoe y lo baraja todo
+    pub fn barajar(&mut self) {
+        self.cartas.append(&mut self.descartes);
+        self.cartas.shuffle(&mut self.rng);
+        self.olvidar_repartidas();
+    }
+
+    fn olvidar_repartidas(&mut self) {
+        self.repartidas.clear();
+        self.vistas = [0; 10];
+    }
+
+    // Vuelve a contar las cartas repartidas, que es lo único que se guarda de ellas
+    pub fn recontar(&mut self) {
+        self.vistas = [0; 10];
+        for carta in &self.repartidas {
+            self.vistas[carta.puntos() as usize - 2] += 1;
+        }
+    }
+
+    // Cada descarte vuelve al shoe en una posición aleatoria, como en una CSM
+    pub fn reinsertar_descartes(&mut self) {
+        for carta in self.descartes.drain(..) {
+            // Con u32 la posición no depende del tamaño de usize de la máquina
+            let posicion = self.rng.gen_range(0..=self.cartas.len() as u32) as usize;
+            self.cartas.insert(posicion, carta);
+        }
+    }
+
+    // Prepara el shoe entre rondas según su tipo.
+    // Devuelve true si se ha llegado a la carta de corte y se ha barajado
+    pub fn terminar_ronda(&mut self) -> bool {
+        match self.tipo {
+            // Las cartas vistas ya han vuelto al shoe: la cuenta empieza de nuevo
+            TipoShoe::Continuo => {
+                self.reinsertar_descartes();
+                self.olvidar_repartidas();
+                false
+            }
+            TipoShoe::CartaCorte if self.necesita_barajar() => {
+                self.barajar();
+                true
+            }
+            TipoShoe::CartaCorte => false,
+        }
+    }
+
+    // Se ha alcanzado la carta de corte
+    pub fn necesita_barajar(&self) -> bool {
+        let repartidas = self.total - self.cartas.len();
+        repartidas as f32 >= self.total as f32 * self.penetracion
+    }
+
+    pub fn restantes(&self) -> usize {
+        self.cartas.len()
+    }
+
+    pub fn en_bandeja(&self) -> usize {
+        self.descartes.len()
+    }
+
+    // Comprueba un shoe leído de una partida guardada antes de robar de él
+    pub fn validar(&self) -> Result<(), String> {
+        let todas = self
+            .cartas
+            .iter()
+            .chain(&self.descartes)
+            .chain(&self.repartidas);
+        if !todas.into_iter().all(Carta::es_valida) {
+            return Err("El shoe tiene una carta que no existe".to_string());
+        }
+        // Lo que queda por repartir y la bandeja no pueden superar las cartas del shoe
+        if self.total == 0
+            || self.cartas.len() + self.descartes.len() > self.total
+            || self.repartidas.len() > self.total
+        {
+            return Err("La posición del shoe no cuadra con sus cartas".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn repartidas(&self) -> &[Carta] {
+        &self.repartidas
+    }
+
+    // Cuenta corriente de todas las cartas repartidas desde el último barajado
+    pub fn cuenta(&self, sistema: SistemaConteo, mazos: u8) -> i32 {
+        cuenta_corriente(sistema, &self.vistas, mazos)
+    }
+
+    pub fn tipo(&self) -> TipoShoe {
+        self.tipo
+    }
+
+    pub fn semilla(&self) -> u64 {
+        self.semilla
+    }
+}
+
+// Semilla aleatoria corta, fácil de copiar en un informe de error o en la configuración
+pub fn nueva_semilla() -> u64 {
+    rand::random::<u32>() as u64
+}
+
+// Shoe con las cartas en el orden dado, sin barajar
+impl From<Vec<Carta>> for Shoe {
+    fn from(cartas: Vec<Carta>) -> Self {
+        let semilla = nueva_semilla();
+        Shoe {
+            total: cartas.len(),
+            cartas,
+            descartes: Vec::new(),
+            repartidas: Vec::new(),
+            vistas: [0; 10],
+            penetracion: 1.0,
+            tipo: TipoShoe::CartaCorte,
+            rng: ChaCha8Rng::seed_from_u64(semilla),
+            semilla,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::deck::Palo;
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: Palo::Tréboles,
+        }
+    }
+
+    #[test]
+    fn test_shoe_varios_mazos() {
+        for mazos in [1, 2, 6, 8] {
+            let shoe = Shoe::nuevo(mazos, 0.75, TipoShoe::CartaCorte);
+            assert_eq!(shoe.total(), mazos as usize * 52);
+            assert_eq!(shoe.restantes(), mazos as usize * 52);
+            assert_eq!(shoe.en_bandeja(), 0);
+        }
+    }
+
+    const ORDEN_SEMILLA_42: [u8; 8] = [11, 6, 1, 5, 9, 11, 8, 8];
+
+    #[test]
+    fn test_misma_semilla_mismo_orden() {
+        let valores = |semilla| {
+            let mut shoe = Shoe::con_semilla(2, 0.75, TipoShoe::CartaCorte, semilla);
+            std::iter::from_fn(|| shoe.robar())
+                .take(20)
+                .map(|c| c.valor)
+                .collect::<Vec<u8>>()
+        };
+        assert_eq!(valores(42), valores(42));
+        assert_ne!(valores(42), valores(43));
+        // El orden de una semilla no cambia entre máquinas ni versiones
+        assert_eq!(valores(42)[..8], ORDEN_SEMILLA_42);
+
+        // Las reinserciones de la barajadora continua también se repiten
+        let continuo = |semilla| {
+            let mut shoe = Shoe::con_semilla(1, 0.75, TipoShoe::Continuo, semilla);
+            let mut valores = Vec::new();
+            for _ in 0..30 {
+                let carta = shoe.robar().unwrap();
+                valores.push(carta.valor);
+                shoe.descartar([carta]);
+                shoe.terminar_ronda();
+            }
+            valores
+        };
+        assert_eq!(continuo(7), continuo(7));
+    }
+
+    #[test]
+    fn test_robar_desde_el_final() {
+        let mut shoe = Shoe::from(vec![carta(2), carta(3)]);
+        assert_eq!(shoe.robar().map(|c| c.valor), Some(3));
+        assert_eq!(shoe.restantes(), 1);
+        assert_eq!(shoe.repartidas().len(), 1);
+    }
+
+    #[test]
+    fn test_carta_de_corte() {
+        let mut shoe = Shoe::nuevo(1, 0.5, TipoShoe::CartaCorte);
+        for _ in 0..25 {
+            let carta = shoe.robar().unwrap();
+            shoe.descartar([carta]);
+        }
+        assert!(!shoe.necesita_barajar());
+        assert_eq!(shoe.en_bandeja(), 25);
+
+        let carta = shoe.robar().unwrap();
+        shoe.descartar([carta]);
+        assert!(shoe.necesita_barajar());
+
+        assert!(shoe.terminar_ronda());
+        assert!(shoe.repartidas().is_empty());
+        assert_eq!(shoe.restantes(), 52);
+        assert_eq!(shoe.en_bandeja(), 0);
+        assert!(!shoe.necesita_barajar());
+    }
+
+    #[test]
+    fn test_shoe_continuo() {
+        let mut shoe = Shoe::nuevo(1, 0.5, TipoShoe::Continuo);
+        for _ in 0..40 {
+            let carta = shoe.robar().unwrap();
+            shoe.descartar([carta]);
+        }
+        assert_eq!(shoe.en_bandeja(), 40);
+
+        // La barajadora continua nunca llega a la carta de corte
+        assert_eq!(shoe.repartidas().len(), 40);
+        assert!(!shoe.terminar_ronda());
+        // Las cartas vistas han vuelto al shoe, así que dejan de contar
+        assert!(shoe.repartidas().is_empty());
+        assert_eq!(shoe.cuenta(SistemaConteo::HiLo, 1), 0);
+        assert_eq!(shoe.restantes(), 52);
+        assert_eq!(shoe.en_bandeja(), 0);
+        assert!(!shoe.necesita_barajar());
+    }
+
+    #[test]
+    fn test_reinsertar_descartes() {
+        let mut shoe = Shoe::from(vec![carta(2), carta(3)]);
+        let carta = shoe.robar().unwrap();
+        shoe.descartar([carta]);
+        shoe.reinsertar_descartes();
+
+        let mut valores: Vec<u8> = std::iter::from_fn(|| shoe.robar())
+            .map(|c| c.valor)
+            .collect();
+        valores.sort();
+        assert_eq!(valores, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_shoe_agotado_rebaraja_descartes() {
+        let mut shoe = Shoe::from(vec![carta(5)]);
+        let carta = shoe.robar().unwrap();
+        shoe.descartar([carta]);
+
+        assert_eq!(shoe.robar().map(|c| c.valor), Some(5));
+        assert!(shoe.robar().is_none());
+    }
+}
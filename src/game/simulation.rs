@@ -0,0 +1,519 @@
+use crate::game::counting::{cuenta_indices, SistemaConteo};
+use crate::game::deviations::TablaIndices;
+use crate::game::logic::{
+    avanzar_mano, banca_puede_tener_blackjack, determinar_ganador, dinero_par, dividir, doblar,
+    jugar_banca, jugar_turno, ofrecer_seguro, puede_pedir, reiniciar_partida, rendirse,
+    repartir_cartas, resolver_seguro, revisar_blackjack, tomar_seguro, Liquidacion, Resultado,
+};
+use crate::game::player::Jugador;
+use crate::game::rules::ReglasMesa;
+use crate::game::shoe::Shoe;
+use crate::game::strategy::{accion_basica, decision_actual, Accion, Opciones, Situacion};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::thread;
+
+// Apuesta de cada ronda simulada: 3:2, 6:5 y la media apuesta salen exactos
+const APUESTA: u32 = 100;
+// Saldo al empezar cada ronda, de sobra para dividir, doblar y asegurar
+const SALDO_RONDA: u32 = 100 * APUESTA;
+
+// Lo que el jugador ve de la mesa al decidir
+pub struct Mesa<'a> {
+    pub reglas: &'a ReglasMesa,
+    pub shoe: &'a Shoe,
+    pub banca: &'a Jugador,
+}
+
+impl Mesa<'_> {
+    // Cuenta de las cartas vistas: la carta oculta de la banca aún no se conoce
+    pub fn cuenta(&self, sistema: SistemaConteo) -> i32 {
+        let mut cuenta = self.shoe.cuenta(sistema, self.reglas.mazos);
+        if let Some(oculta) = self.banca.mano().cartas.get(1) {
+            cuenta -= sistema.valor(oculta);
+        }
+        cuenta
+    }
+}
+
+// Forma de jugar de un jugador simulado
+pub trait Estrategia {
+    fn decidir(&mut self, situacion: Situacion, opciones: Opciones, mesa: &Mesa) -> Accion;
+
+    // Seguro, o dinero par con blackjack, cuando la banca muestra un as
+    fn tomar_seguro(&mut self, _mesa: &Mesa) -> bool {
+        false
+    }
+}
+
+// Estrategia básica sin contar cartas
+pub struct EstrategiaBasica;
+
+impl Estrategia for EstrategiaBasica {
+    fn decidir(&mut self, situacion: Situacion, opciones: Opciones, mesa: &Mesa) -> Accion {
+        accion_basica(situacion, mesa.reglas, opciones)
+    }
+}
+
+// Estrategia básica con las jugadas de índice según la cuenta
+pub struct EstrategiaIndices {
+    pub tabla: TablaIndices,
+}
+
+impl EstrategiaIndices {
+    fn cuenta(&self, mesa: &Mesa) -> f64 {
+        let sistema = self.tabla.sistema;
+        cuenta_indices(sistema, mesa.cuenta(sistema), mesa.shoe.restantes())
+    }
+}
+
+impl Estrategia for EstrategiaIndices {
+    fn decidir(&mut self, situacion: Situacion, opciones: Opciones, mesa: &Mesa) -> Accion {
+        let cuenta = self.cuenta(mesa);
+        self.tabla
+            .recomendar(situacion, mesa.reglas, opciones, cuenta)
+            .0
+    }
+
+    fn tomar_seguro(&mut self, mesa: &Mesa) -> bool {
+        self.tabla.tomar_seguro(self.cuenta(mesa))
+    }
+}
+
+// Juega como la banca: pide por debajo de 17 y nunca dobla ni divide
+pub struct ImitarBanca;
+
+impl Estrategia for ImitarBanca {
+    fn decidir(&mut self, situacion: Situacion, _opciones: Opciones, _mesa: &Mesa) -> Accion {
+        if situacion.total < 17 {
+            Accion::Pedir
+        } else {
+            Accion::Plantarse
+        }
+    }
+}
+
+// Resultado de una ronda simulada
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ronda {
+    pub liquidacion: Liquidacion,
+    // Ganancia neta en apuestas iniciales
+    pub neto: f64,
+    pub blackjack: bool,
+}
+
+// Juega una ronda completa con la misma secuencia que la partida interactiva
+pub fn jugar_ronda(
+    jugador: &mut Jugador,
+    banca: &mut Jugador,
+    shoe: &mut Shoe,
+    reglas: &ReglasMesa,
+    estrategia: &mut impl Estrategia,
+) -> Ronda {
+    jugador.saldo = SALDO_RONDA - APUESTA;
+    jugador.limpiar_manos(APUESTA);
+    repartir_cartas(jugador, banca, shoe, reglas);
+    let blackjack = jugador.mano().es_blackjack();
+
+    // Decisiones previas al peek: seguro o dinero par, y rendición temprana
+    let mut liquidacion = None;
+    if ofrecer_seguro(banca, reglas)
+        && estrategia.tomar_seguro(&Mesa {
+            reglas,
+            shoe,
+            banca,
+        })
+    {
+        if blackjack {
+            liquidacion = dinero_par(jugador);
+        } else {
+            tomar_seguro(jugador);
+        }
+    }
+    if liquidacion.is_none() && banca_puede_tener_blackjack(banca) {
+        if let Some((situacion, opciones)) = decision_actual(jugador, banca, reglas, true) {
+            let mesa = Mesa {
+                reglas,
+                shoe,
+                banca,
+            };
+            if opciones.rendirse
+                && estrategia.decidir(situacion, opciones, &mesa) == Accion::Rendirse
+            {
+                rendirse(jugador, reglas, true);
+            }
+        }
+    }
+
+    if liquidacion.is_none() {
+        if !reglas.peek.sin_carta_oculta() {
+            resolver_seguro(jugador, banca);
+        }
+        liquidacion = revisar_blackjack(jugador, banca, reglas);
+    }
+
+    // Turno del jugador. Una jugada no permitida se toma como plantarse
+    while liquidacion.is_none() && !jugador.turno_terminado() {
+        let Some((situacion, opciones)) = decision_actual(jugador, banca, reglas, false) else {
+            avanzar_mano(jugador, shoe, reglas);
+            continue;
+        };
+        let accion = estrategia.decidir(
+            situacion,
+            opciones,
+            &Mesa {
+                reglas,
+                shoe,
+                banca,
+            },
+        );
+        let hecha = match accion {
+            Accion::Pedir if puede_pedir(jugador) => {
+                jugar_turno(jugador, shoe, true);
+                if jugador.mano().pasada() {
+                    avanzar_mano(jugador, shoe, reglas);
+                }
+                true
+            }
+            Accion::Doblar => doblar(jugador, shoe, reglas),
+            Accion::Dividir => dividir(jugador, shoe, reglas),
+            Accion::Rendirse => rendirse(jugador, reglas, false),
+            Accion::Pedir | Accion::Plantarse => false,
+        };
+        if !hecha {
+            avanzar_mano(jugador, shoe, reglas);
+        }
+    }
+
+    let liquidacion = liquidacion.unwrap_or_else(|| {
+        // Con carta oculta ya revisada, la banca no juega si ninguna mano la necesita
+        let sin_banca = !jugador.necesita_banca() && !reglas.peek.sin_carta_oculta();
+        if !jugador.todas_pasadas() && !sin_banca {
+            jugar_banca(jugador, banca, shoe, reglas);
+        }
+        determinar_ganador(jugador, banca, reglas)
+    });
+
+    let neto = (jugador.saldo as f64 - SALDO_RONDA as f64) / APUESTA as f64;
+    reiniciar_partida(jugador, banca, shoe);
+    Ronda {
+        liquidacion,
+        neto,
+        blackjack,
+    }
+}
+
+// Resultados acumulados de una simulación
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Estadisticas {
+    pub rondas: u64,
+    pub manos: u64,
+    pub ganadas: u64,
+    pub empates: u64,
+    pub perdidas: u64,
+    pub blackjacks: u64,
+    // Suma de la ganancia por ronda y de sus cuadrados, en apuestas iniciales
+    pub suma: f64,
+    pub suma_cuadrados: f64,
+}
+
+impl Estadisticas {
+    pub fn anotar(&mut self, ronda: &Ronda) {
+        self.rondas += 1;
+        if ronda.blackjack {
+            self.blackjacks += 1;
+        }
+        for resultado in &ronda.liquidacion.resultados {
+            self.manos += 1;
+            match resultado {
+                Resultado::Blackjack
+                | Resultado::Gana
+                | Resultado::BancaSePasa
+                | Resultado::DineroPar => self.ganadas += 1,
+                Resultado::Empate => self.empates += 1,
+                Resultado::Pierde
+                | Resultado::SePasa
+                | Resultado::BlackjackBanca
+                | Resultado::Rendicion => self.perdidas += 1,
+            }
+        }
+        self.suma += ronda.neto;
+        self.suma_cuadrados += ronda.neto * ronda.neto;
+    }
+
+    pub fn combinar(&mut self, otras: &Estadisticas) {
+        self.rondas += otras.rondas;
+        self.manos += otras.manos;
+        self.ganadas += otras.ganadas;
+        self.empates += otras.empates;
+        self.perdidas += otras.perdidas;
+        self.blackjacks += otras.blackjacks;
+        self.suma += otras.suma;
+        self.suma_cuadrados += otras.suma_cuadrados;
+    }
+
+    // Ganancia media del jugador por ronda, en apuestas iniciales
+    pub fn media(&self) -> f64 {
+        if self.rondas == 0 {
+            0.0
+        } else {
+            self.suma / self.rondas as f64
+        }
+    }
+
+    // Ventaja de la casa: lo que pierde el jugador por cada apuesta inicial
+    pub fn ventaja_casa(&self) -> f64 {
+        -self.media()
+    }
+
+    // Desviación típica de la ganancia de una ronda, es decir, por mano inicial: las
+    // manos divididas, los dobles y el seguro cuentan dentro de la misma mano, como en
+    // la desviación por mano que se suele dar en blackjack (unas 1,15 apuestas)
+    pub fn desviacion(&self) -> f64 {
+        if self.rondas < 2 {
+            return 0.0;
+        }
+        let n = self.rondas as f64;
+        let varianza = (self.suma_cuadrados - self.suma * self.suma / n) / (n - 1.0);
+        varianza.max(0.0).sqrt()
+    }
+
+    // Error estándar de la ventaja de la casa
+    pub fn error_estandar(&self) -> f64 {
+        if self.rondas == 0 {
+            0.0
+        } else {
+            self.desviacion() / (self.rondas as f64).sqrt()
+        }
+    }
+
+    // Intervalo de confianza de la ventaja de la casa para el valor z dado (1.96 para el 95%)
+    pub fn intervalo(&self, z: f64) -> (f64, f64) {
+        let margen = z * self.error_estandar();
+        (self.ventaja_casa() - margen, self.ventaja_casa() + margen)
+    }
+
+    // Fracción de las manos jugadas
+    pub fn tasa(&self, manos: u64) -> f64 {
+        if self.manos == 0 {
+            0.0
+        } else {
+            manos as f64 / self.manos as f64
+        }
+    }
+
+    // Blackjacks del jugador por ronda
+    pub fn frecuencia_blackjack(&self) -> f64 {
+        if self.rondas == 0 {
+            0.0
+        } else {
+            self.blackjacks as f64 / self.rondas as f64
+        }
+    }
+}
+
+// Parámetros de una simulación. Con la misma semilla y el mismo número
+// de hilos se repiten exactamente los resultados
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Simulacion {
+    pub rondas: u64,
+    pub hilos: usize,
+    pub semilla: u64,
+}
+
+// Semilla del shoe de cada hilo. Salen de un generador propio para que semillas
+// vecinas no compartan hilos: con sumas, el hilo 1 de la semilla 7 sería el hilo 0 de la 8
+fn semillas_hilos(semilla: u64, hilos: u64) -> Vec<u64> {
+    let mut rng = ChaCha8Rng::seed_from_u64(semilla);
+    (0..hilos).map(|_| rng.gen()).collect()
+}
+
+// Reparte las rondas entre varios hilos, cada uno con su shoe y su semilla
+pub fn simular<E: Estrategia>(
+    reglas: &ReglasMesa,
+    simulacion: Simulacion,
+    crear_estrategia: impl Fn() -> E + Sync,
+) -> Estadisticas {
+    let hilos = simulacion.hilos.max(1) as u64;
+    let crear_estrategia = &crear_estrategia;
+    let semillas = semillas_hilos(simulacion.semilla, hilos);
+
+    thread::scope(|scope| {
+        let trabajos: Vec<_> = (0..hilos)
+            .zip(semillas)
+            .map(|(hilo, semilla)| {
+                // El primer hilo se queda con las rondas que sobran del reparto
+                let rondas = simulacion.rondas / hilos
+                    + if hilo == 0 {
+                        simulacion.rondas % hilos
+                    } else {
+                        0
+                    };
+                scope.spawn(move || {
+                    let mut estrategia = crear_estrategia();
+                    let mut shoe = Shoe::con_semilla(
+                        reglas.mazos,
+                        reglas.penetracion,
+                        reglas.tipo_shoe,
+                        semilla,
+                    );
+                    let mut jugador = Jugador::nuevo();
+                    let mut banca = Jugador::nuevo();
+                    let mut estadisticas = Estadisticas::default();
+                    for _ in 0..rondas {
+                        let ronda = jugar_ronda(
+                            &mut jugador,
+                            &mut banca,
+                            &mut shoe,
+                            reglas,
+                            &mut estrategia,
+                        );
+                        estadisticas.anotar(&ronda);
+                    }
+                    estadisticas
+                })
+            })
+            .collect();
+
+        trabajos
+            .into_iter()
+            .fold(Estadisticas::default(), |mut total, trabajo| {
+                total.combinar(&trabajo.join().expect("un hilo de la simulación ha fallado"));
+                total
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::deck::{Carta, Palo};
+
+    // Shoe que reparte las cartas en el orden dado
+    fn shoe(valores: &[u8]) -> Shoe {
+        Shoe::from(
+            valores
+                .iter()
+                .rev()
+                .map(|&valor| Carta {
+                    valor,
+                    palo: Palo::Picas,
+                })
+                .collect::<Vec<Carta>>(),
+        )
+    }
+
+    fn ronda(valores: &[u8], estrategia: &mut impl Estrategia) -> Ronda {
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        jugar_ronda(
+            &mut jugador,
+            &mut banca,
+            &mut shoe(valores),
+            &ReglasMesa::default(),
+            estrategia,
+        )
+    }
+
+    #[test]
+    fn test_jugar_ronda() {
+        // Blackjack del jugador: 3:2
+        let blackjack = ronda(&[1, 9, 13, 7], &mut EstrategiaBasica);
+        assert_eq!(blackjack.neto, 1.5);
+        assert!(blackjack.blackjack);
+
+        // 11 contra 6: dobla con un 10 y la banca se pasa (6+10+10)
+        let doble = ronda(&[5, 6, 6, 10, 10, 10], &mut EstrategiaBasica);
+        assert_eq!(doble.liquidacion.resultados, vec![Resultado::BancaSePasa]);
+        assert_eq!(doble.neto, 2.0);
+
+        // 16 contra 10: la rendición tardía devuelve media apuesta
+        let rendida = ronda(&[10, 10, 6, 8], &mut EstrategiaBasica);
+        assert_eq!(rendida.liquidacion.resultados, vec![Resultado::Rendicion]);
+        assert_eq!(rendida.neto, -0.5);
+
+        // Imitando a la banca se pide con 16 y se pasa
+        let pasada = ronda(&[10, 10, 6, 8, 9], &mut ImitarBanca);
+        assert_eq!(pasada.liquidacion.resultados, vec![Resultado::SePasa]);
+        assert_eq!(pasada.neto, -1.0);
+    }
+
+    #[test]
+    fn test_estadisticas() {
+        let mut estadisticas = Estadisticas::default();
+        for (resultado, neto) in [
+            (Resultado::Gana, 1.0),
+            (Resultado::Pierde, -1.0),
+            (Resultado::Empate, 0.0),
+            (Resultado::Pierde, -1.0),
+        ] {
+            estadisticas.anotar(&Ronda {
+                liquidacion: Liquidacion {
+                    resultados: vec![resultado],
+                    seguro: None,
+                },
+                neto,
+                blackjack: false,
+            });
+        }
+        assert_eq!(estadisticas.ventaja_casa(), 0.25);
+        assert_eq!(estadisticas.tasa(estadisticas.perdidas), 0.5);
+        assert!((estadisticas.desviacion() - (2.75f64 / 3.0).sqrt()).abs() < 1e-12);
+
+        let (inferior, superior) = estadisticas.intervalo(1.96);
+        assert!(inferior < 0.25 && superior > 0.25);
+        assert!((superior - 0.25 - 1.96 * estadisticas.error_estandar()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_simulacion_reproducible() {
+        let reglas = ReglasMesa::default();
+        let simulacion = Simulacion {
+            rondas: 2001,
+            hilos: 2,
+            semilla: 7,
+        };
+        let a = simular(&reglas, simulacion, || EstrategiaBasica);
+        let b = simular(&reglas, simulacion, || EstrategiaBasica);
+        assert_eq!(a, b);
+        assert_eq!(a.rondas, 2001);
+        assert_eq!(a.ganadas + a.empates + a.perdidas, a.manos);
+
+        let c = simular(
+            &reglas,
+            Simulacion {
+                semilla: 8,
+                ..simulacion
+            },
+            || EstrategiaBasica,
+        );
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_semillas_hilos() {
+        let siete = semillas_hilos(7, 4);
+        let ocho = semillas_hilos(8, 4);
+        assert_eq!(siete, semillas_hilos(7, 4));
+        // Más hilos no cambian la semilla de los primeros
+        assert_eq!(siete[..2], semillas_hilos(7, 2));
+        assert!(siete.iter().all(|semilla| !ocho.contains(semilla)));
+    }
+
+    #[test]
+    fn test_estrategia_basica_frente_a_imitar_banca() {
+        let reglas = ReglasMesa::default();
+        let simulacion = Simulacion {
+            rondas: 40_000,
+            hilos: 4,
+            semilla: 1,
+        };
+        let basica = simular(&reglas, simulacion, || EstrategiaBasica);
+        let banca = simular(&reglas, simulacion, || ImitarBanca);
+
+        // Con estrategia básica la ventaja de la casa ronda el 0,5%; imitando a la banca, el 5%
+        assert!(basica.ventaja_casa().abs() < 0.03);
+        assert!(banca.ventaja_casa() > basica.ventaja_casa() + 0.02);
+        assert!((basica.frecuencia_blackjack() - 0.047).abs() < 0.01);
+    }
+}
@@ -0,0 +1,479 @@
+use crate::game::deck::Carta;
+use crate::game::hand::Mano;
+use crate::game::logic::{puede_dividir, puede_doblar, puede_rendirse};
+use crate::game::player::Jugador;
+use crate::game::rules::{ReglaBanca, ReglaPeek, ReglasMesa, Rendicion};
+use serde::{Deserialize, Serialize};
+
+// Jugadas posibles en una decisión
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Accion {
+    Pedir,
+    Plantarse,
+    Doblar,
+    Dividir,
+    Rendirse,
+}
+
+impl Accion {
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            Accion::Pedir => "Pedir carta",
+            Accion::Plantarse => "Plantarse",
+            Accion::Doblar => "Doblar",
+            Accion::Dividir => "Dividir",
+            Accion::Rendirse => "Rendirse",
+        }
+    }
+
+    // Letra con la que aparece en las tablas de estrategia básica
+    pub fn letra(&self) -> char {
+        match self {
+            Accion::Pedir => 'H',
+            Accion::Plantarse => 'S',
+            Accion::Doblar => 'D',
+            Accion::Dividir => 'P',
+            Accion::Rendirse => 'R',
+        }
+    }
+}
+
+// Mano del jugador frente a la carta visible de la banca (2..=11, el as vale 11)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Situacion {
+    pub total: u8,
+    pub blanda: bool,
+    // Valor de la carta repetida si la mano es una pareja
+    pub par: Option<u8>,
+    pub banca: u8,
+}
+
+impl Situacion {
+    pub fn nueva(mano: &Mano, carta_banca: &Carta) -> Self {
+        let valor = mano.valor();
+        Situacion {
+            total: valor.total,
+            blanda: valor.blanda,
+            par: mano.es_par().then(|| mano.cartas[0].puntos()),
+            banca: carta_banca.puntos(),
+        }
+    }
+
+    // Descripción corta de la mano, p. ej. "16 duro vs 10" o "8-8 vs A"
+    pub fn descripcion(&self) -> String {
+        let banca = match self.banca {
+            11 => "A".to_string(),
+            carta => carta.to_string(),
+        };
+        match (self.par, self.blanda) {
+            (Some(11), _) => format!("A-A vs {}", banca),
+            (Some(carta), _) => format!("{}-{} vs {}", carta, carta, banca),
+            (None, true) => format!("{} blando vs {}", self.total, banca),
+            (None, false) => format!("{} duro vs {}", self.total, banca),
+        }
+    }
+}
+
+// Acciones permitidas además de pedir y plantarse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opciones {
+    pub doblar: bool,
+    pub dividir: bool,
+    pub rendirse: bool,
+}
+
+// Situación y jugadas permitidas en la decisión actual del jugador
+pub fn decision_actual(
+    jugador: &Jugador,
+    banca: &Jugador,
+    reglas: &ReglasMesa,
+    antes_del_peek: bool,
+) -> Option<(Situacion, Opciones)> {
+    let carta_banca = banca.mano().cartas.first()?;
+    let mano = jugador.mano();
+    if mano.terminada || mano.cartas.len() < 2 {
+        return None;
+    }
+
+    let opciones = Opciones {
+        doblar: !antes_del_peek && puede_doblar(jugador, reglas),
+        dividir: !antes_del_peek && puede_dividir(jugador, reglas),
+        rendirse: puede_rendirse(jugador, reglas, antes_del_peek),
+    };
+    Some((Situacion::nueva(mano, carta_banca), opciones))
+}
+
+// Jugada de estrategia básica para la decisión actual del jugador
+pub fn recomendar(
+    jugador: &Jugador,
+    banca: &Jugador,
+    reglas: &ReglasMesa,
+    antes_del_peek: bool,
+) -> Option<Accion> {
+    decision_actual(jugador, banca, reglas, antes_del_peek)
+        .map(|(situacion, opciones)| accion_basica(situacion, reglas, opciones))
+}
+
+// Estrategia básica ajustada a S17/H17, doblar tras dividir, rendición, un solo mazo y
+// banca sin carta oculta. Con dos mazos se usa la tabla de varios mazos, que solo
+// difiere en jugadas muy ajustadas
+pub fn accion_basica(situacion: Situacion, reglas: &ReglasMesa, opciones: Opciones) -> Accion {
+    if opciones.rendirse && debe_rendirse(situacion, reglas) {
+        return Accion::Rendirse;
+    }
+    if opciones.dividir {
+        if let Some(carta) = situacion.par {
+            if debe_dividir(carta, situacion.banca, reglas) {
+                return Accion::Dividir;
+            }
+        }
+    }
+
+    let banca = situacion.banca;
+    let h17 = reglas.banca == ReglaBanca::H17;
+    let un_mazo = reglas.mazos == 1;
+    // Con un mazo, 7-7 contra un 10 se planta: quedan pocos sietes para llegar a 21
+    if un_mazo && situacion.par == Some(7) && banca == 10 {
+        return Accion::Plantarse;
+    }
+    // Dobla si puede; si no, pide (D) o se planta (Ds)
+    let doblar_o = |alternativa: Accion| {
+        if opciones.doblar {
+            Accion::Doblar
+        } else {
+            alternativa
+        }
+    };
+
+    if situacion.blanda {
+        return match situacion.total {
+            13 | 14 if (5..=6).contains(&banca) || (un_mazo && banca == 4) => {
+                doblar_o(Accion::Pedir)
+            }
+            15 | 16 if (4..=6).contains(&banca) => doblar_o(Accion::Pedir),
+            17 if (3..=6).contains(&banca) || (un_mazo && banca == 2) => doblar_o(Accion::Pedir),
+            18 if (3..=6).contains(&banca) || (h17 && banca == 2) => doblar_o(Accion::Plantarse),
+            18 if (2..=8).contains(&banca) || (un_mazo && !h17 && banca == 11) => Accion::Plantarse,
+            19 if (h17 || un_mazo) && banca == 6 => doblar_o(Accion::Plantarse),
+            ..=18 => Accion::Pedir,
+            _ => Accion::Plantarse,
+        };
+    }
+
+    match situacion.total {
+        8 if un_mazo && (5..=6).contains(&banca) => doblar_o(Accion::Pedir),
+        9 if (3..=6).contains(&banca) || (un_mazo && banca == 2) => doblar_o(Accion::Pedir),
+        10 if banca <= 9 => doblar_o(Accion::Pedir),
+        // Sin carta oculta lo doblado se pierde contra un blackjack de la banca
+        11 if sin_carta_oculta(reglas) && banca >= 10 => Accion::Pedir,
+        11 if banca <= 10 || h17 || un_mazo => doblar_o(Accion::Pedir),
+        12 if (4..=6).contains(&banca) => Accion::Plantarse,
+        13..=16 if banca <= 6 => Accion::Plantarse,
+        ..=16 => Accion::Pedir,
+        _ => Accion::Plantarse,
+    }
+}
+
+// Si un blackjack de la banca se lleva también lo doblado y lo dividido (ENHC)
+fn sin_carta_oculta(reglas: &ReglasMesa) -> bool {
+    reglas.peek == ReglaPeek::Europea
+}
+
+fn debe_dividir(carta: u8, banca: u8, reglas: &ReglasMesa) -> bool {
+    let das = reglas.doblar_tras_dividir;
+    let un_mazo = reglas.mazos == 1;
+    let enhc = sin_carta_oculta(reglas);
+    match carta {
+        11 => !(enhc && banca == 11),
+        8 => !(enhc && banca >= 10),
+        10 | 5 => false,
+        9 => banca <= 9 && banca != 7,
+        7 => banca <= 7 || (un_mazo && das && banca == 8),
+        6 => (banca <= 6 && (das || un_mazo || banca >= 3)) || (un_mazo && das && banca == 7),
+        4 => das && (if un_mazo { 4 } else { 5 }..=6).contains(&banca),
+        3 if un_mazo && das => banca <= 8,
+        3 => banca <= 7 && (das || banca >= 4),
+        _ => banca <= 7 && (das || banca >= if un_mazo { 3 } else { 4 }),
+    }
+}
+
+fn debe_rendirse(situacion: Situacion, reglas: &ReglasMesa) -> bool {
+    if situacion.blanda {
+        return false;
+    }
+    let total = situacion.total;
+    let par = situacion.par;
+    match (reglas.rendicion, situacion.banca) {
+        (Rendicion::No, _) => false,
+        // La rendición temprana también evita perder contra un blackjack de la banca
+        (Rendicion::Temprana, 11) => matches!(total, 5..=7 | 12..=17),
+        (Rendicion::Temprana, 10) => matches!(total, 14..=16),
+        (Rendicion::Temprana, 9) => total == 16 && par.is_none(),
+        (_, banca) => {
+            let h17 = reglas.banca == ReglaBanca::H17;
+            match total {
+                16 => par.is_none() && banca >= 9,
+                15 => banca == 10 || (h17 && banca == 11),
+                17 => h17 && banca == 11,
+                _ => false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::PerfilMesa;
+
+    const TODAS: Opciones = Opciones {
+        doblar: true,
+        dividir: true,
+        rendirse: true,
+    };
+
+    fn dura(total: u8, banca: u8) -> Situacion {
+        Situacion {
+            total,
+            blanda: false,
+            par: None,
+            banca,
+        }
+    }
+
+    fn blanda(total: u8, banca: u8) -> Situacion {
+        Situacion {
+            blanda: true,
+            ..dura(total, banca)
+        }
+    }
+
+    fn pareja(carta: u8, banca: u8) -> Situacion {
+        Situacion {
+            total: if carta == 11 { 12 } else { carta * 2 },
+            blanda: carta == 11,
+            par: Some(carta),
+            banca,
+        }
+    }
+
+    #[test]
+    fn test_totales_duros() {
+        let reglas = ReglasMesa::default();
+        assert_eq!(accion_basica(dura(8, 6), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(accion_basica(dura(11, 10), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(dura(11, 11), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(accion_basica(dura(12, 3), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(
+            accion_basica(dura(12, 4), &reglas, TODAS),
+            Accion::Plantarse
+        );
+        assert_eq!(accion_basica(dura(16, 7), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(
+            accion_basica(dura(16, 10), &reglas, TODAS),
+            Accion::Rendirse
+        );
+        assert_eq!(
+            accion_basica(dura(17, 11), &reglas, TODAS),
+            Accion::Plantarse
+        );
+    }
+
+    #[test]
+    fn test_totales_blandos() {
+        let reglas = ReglasMesa::default();
+        assert_eq!(accion_basica(blanda(13, 5), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(blanda(17, 2), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(
+            accion_basica(blanda(18, 2), &reglas, TODAS),
+            Accion::Plantarse
+        );
+        assert_eq!(accion_basica(blanda(18, 9), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(
+            accion_basica(blanda(19, 6), &reglas, TODAS),
+            Accion::Plantarse
+        );
+    }
+
+    #[test]
+    fn test_parejas() {
+        let reglas = ReglasMesa::default();
+        assert_eq!(
+            accion_basica(pareja(11, 11), &reglas, TODAS),
+            Accion::Dividir
+        );
+        assert_eq!(
+            accion_basica(pareja(8, 10), &reglas, TODAS),
+            Accion::Dividir
+        );
+        assert_eq!(
+            accion_basica(pareja(10, 6), &reglas, TODAS),
+            Accion::Plantarse
+        );
+        assert_eq!(
+            accion_basica(pareja(9, 7), &reglas, TODAS),
+            Accion::Plantarse
+        );
+        assert_eq!(accion_basica(pareja(5, 9), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(pareja(2, 2), &reglas, TODAS), Accion::Dividir);
+
+        // Sin doblar tras dividir se divide menos
+        let sin_das = ReglasMesa {
+            doblar_tras_dividir: false,
+            ..ReglasMesa::default()
+        };
+        assert_eq!(accion_basica(pareja(2, 2), &sin_das, TODAS), Accion::Pedir);
+        assert_eq!(accion_basica(pareja(4, 5), &sin_das, TODAS), Accion::Pedir);
+    }
+
+    #[test]
+    fn test_ajustes_h17() {
+        let reglas = ReglasMesa {
+            banca: ReglaBanca::H17,
+            ..ReglasMesa::default()
+        };
+        assert_eq!(accion_basica(dura(11, 11), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(blanda(19, 6), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(
+            accion_basica(dura(15, 11), &reglas, TODAS),
+            Accion::Rendirse
+        );
+    }
+
+    #[test]
+    fn test_sin_opciones() {
+        let reglas = ReglasMesa::default();
+        let ninguna = Opciones {
+            doblar: false,
+            dividir: false,
+            rendirse: false,
+        };
+        assert_eq!(accion_basica(dura(11, 6), &reglas, ninguna), Accion::Pedir);
+        assert_eq!(
+            accion_basica(blanda(18, 4), &reglas, ninguna),
+            Accion::Plantarse
+        );
+        assert_eq!(
+            accion_basica(pareja(8, 10), &reglas, ninguna),
+            Accion::Pedir
+        );
+        assert_eq!(accion_basica(dura(16, 10), &reglas, ninguna), Accion::Pedir);
+
+        let europea = PerfilMesa::Europea.reglas();
+        assert_eq!(accion_basica(dura(16, 10), &europea, TODAS), Accion::Pedir);
+    }
+
+    #[test]
+    fn test_sin_carta_oculta() {
+        // Europea: sin carta oculta no se arriesga más dinero contra un 10 o un as
+        let reglas = PerfilMesa::Europea.reglas();
+        assert_eq!(accion_basica(dura(11, 10), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(accion_basica(dura(11, 9), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(pareja(8, 10), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(accion_basica(pareja(8, 11), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(accion_basica(pareja(8, 9), &reglas, TODAS), Accion::Dividir);
+        assert_eq!(accion_basica(pareja(11, 11), &reglas, TODAS), Accion::Pedir);
+        assert_eq!(
+            accion_basica(pareja(11, 10), &reglas, TODAS),
+            Accion::Dividir
+        );
+
+        // Si solo se pierde la apuesta original se juega como con carta oculta
+        let obo = ReglasMesa {
+            peek: ReglaPeek::EuropeaApuestaOriginal,
+            ..reglas
+        };
+        assert_eq!(accion_basica(dura(11, 10), &obo, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(pareja(8, 11), &obo, TODAS), Accion::Dividir);
+    }
+
+    #[test]
+    fn test_un_mazo() {
+        // Downtown: un mazo, H17 y sin doblar tras dividir
+        let reglas = PerfilMesa::Downtown.reglas();
+        assert_eq!(accion_basica(dura(8, 5), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(dura(9, 2), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(dura(11, 11), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(blanda(13, 4), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(blanda(17, 2), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(blanda(19, 6), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(accion_basica(pareja(2, 3), &reglas, TODAS), Accion::Dividir);
+        assert_eq!(accion_basica(pareja(6, 2), &reglas, TODAS), Accion::Dividir);
+        assert_eq!(accion_basica(pareja(4, 5), &reglas, TODAS), Accion::Doblar);
+        assert_eq!(
+            accion_basica(pareja(7, 10), &reglas, TODAS),
+            Accion::Plantarse
+        );
+
+        // Con S17 el 18 blando se planta contra el as
+        let s17 = ReglasMesa {
+            banca: ReglaBanca::S17,
+            ..reglas.clone()
+        };
+        assert_eq!(
+            accion_basica(blanda(18, 11), &s17, TODAS),
+            Accion::Plantarse
+        );
+        assert_eq!(accion_basica(blanda(18, 11), &reglas, TODAS), Accion::Pedir);
+
+        // Con doblar tras dividir se divide más
+        let das = ReglasMesa {
+            doblar_tras_dividir: true,
+            ..reglas
+        };
+        assert_eq!(accion_basica(pareja(7, 8), &das, TODAS), Accion::Dividir);
+        assert_eq!(accion_basica(pareja(3, 8), &das, TODAS), Accion::Dividir);
+        assert_eq!(accion_basica(pareja(4, 4), &das, TODAS), Accion::Dividir);
+    }
+
+    #[test]
+    fn test_rendicion_temprana() {
+        let reglas = ReglasMesa {
+            rendicion: Rendicion::Temprana,
+            ..ReglasMesa::default()
+        };
+        assert_eq!(
+            accion_basica(dura(14, 10), &reglas, TODAS),
+            Accion::Rendirse
+        );
+        assert_eq!(accion_basica(dura(7, 11), &reglas, TODAS), Accion::Rendirse);
+        assert_eq!(
+            accion_basica(pareja(8, 10), &reglas, TODAS),
+            Accion::Rendirse
+        );
+        assert_eq!(
+            accion_basica(pareja(11, 11), &reglas, TODAS),
+            Accion::Dividir
+        );
+    }
+
+    #[test]
+    fn test_recomendar() {
+        let reglas = ReglasMesa::default();
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        jugador.mano_mut().apuesta = 10;
+        jugador.mano_mut().cartas = vec![carta(6), carta(5)];
+        banca.mano_mut().cartas = vec![carta(6)];
+
+        assert_eq!(
+            recomendar(&jugador, &banca, &reglas, false),
+            Some(Accion::Doblar)
+        );
+
+        // Sin saldo para doblar, la recomendación pasa a pedir
+        jugador.saldo = 0;
+        assert_eq!(
+            recomendar(&jugador, &banca, &reglas, false),
+            Some(Accion::Pedir)
+        );
+    }
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: crate::game::deck::Palo::Picas,
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::game::hand::Mano;
+use crate::game::history::RondaJugada;
+use crate::game::profile::EstadisticasPerfil;
+
+// Primer total inicial que se puede tener con dos cartas (2-2)
+pub const TOTAL_MINIMO: u8 = 4;
+
+// Resultados del historial agrupados para el panel de estadísticas
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tendencias {
+    // Ganancia acumulada tras cada ronda del historial, contando desde 1
+    pub acumulado: Vec<(f64, f64)>,
+    // Saldo al empezar la última sesión y tras cada una de sus rondas
+    pub saldo_sesion: Vec<u64>,
+    pub total: EstadisticasPerfil,
+    // Por carta visible de la banca: del 2 (posición 0) al as (posición 9)
+    pub por_carta_banca: [EstadisticasPerfil; 10],
+    // Por total de las dos primeras cartas, de TOTAL_MINIMO a 21
+    pub por_total: [EstadisticasPerfil; 18],
+    // Por mesa, en el orden en que se jugaron por primera vez
+    pub por_mesa: Vec<(String, EstadisticasPerfil)>,
+}
+
+impl Tendencias {
+    pub fn nuevas(rondas: &[RondaJugada]) -> Self {
+        let mut tendencias = Tendencias::default();
+        let mut acumulado = 0;
+
+        for (i, ronda) in rondas.iter().enumerate() {
+            acumulado += ronda.neto;
+            tendencias
+                .acumulado
+                .push(((i + 1) as f64, acumulado as f64));
+
+            // Las rondas de una partida nueva vuelven a contar desde 1
+            if i == 0 || ronda.ronda <= rondas[i - 1].ronda {
+                tendencias.saldo_sesion = vec![ronda.saldo_inicial as u64];
+            }
+            tendencias.saldo_sesion.push(ronda.saldo_final as u64);
+
+            tendencias.total.anotar(ronda);
+            if let Some(carta) = ronda.banca.first() {
+                tendencias.por_carta_banca[carta.puntos() as usize - 2].anotar(ronda);
+            }
+            let mut inicial = Mano::nueva(0);
+            inicial.cartas = ronda.jugador.clone();
+            let total = inicial.valor().total;
+            if let Some(grupo) = total
+                .checked_sub(TOTAL_MINIMO)
+                .and_then(|i| tendencias.por_total.get_mut(i as usize))
+            {
+                grupo.anotar(ronda);
+            }
+
+            let nombre = &ronda.reglas.nombre;
+            match tendencias
+                .por_mesa
+                .iter_mut()
+                .find(|(mesa, _)| mesa == nombre)
+            {
+                Some((_, grupo)) => grupo.anotar(ronda),
+                None => {
+                    let mut grupo = EstadisticasPerfil::default();
+                    grupo.anotar(ronda);
+                    tendencias.por_mesa.push((nombre.clone(), grupo));
+                }
+            }
+        }
+        tendencias
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::deck::{Carta, Palo};
+    use crate::game::history::ManoJugada;
+    use crate::game::logic::Resultado;
+    use crate::game::rules::{PerfilMesa, ReglasMesa};
+
+    fn carta(valor: u8) -> Carta {
+        Carta {
+            valor,
+            palo: Palo::Corazones,
+        }
+    }
+
+    fn ronda(
+        numero: u32,
+        reglas: &ReglasMesa,
+        jugador: [u8; 2],
+        banca: u8,
+        resultado: Resultado,
+        neto: i64,
+    ) -> RondaJugada {
+        RondaJugada {
+            ronda: numero,
+            fecha: 0,
+            semilla: 0,
+            reglas: reglas.clone(),
+            posicion: 0,
+            apuesta: 10,
+            seguro: 0,
+            jugador: jugador.iter().map(|&valor| carta(valor)).collect(),
+            banca: vec![carta(banca), carta(10)],
+            acciones: Vec::new(),
+            robadas_banca: Vec::new(),
+            manos: vec![ManoJugada {
+                cartas: Vec::new(),
+                apuesta: 10,
+                doblada: false,
+                resultado,
+            }],
+            saldo_inicial: 1000,
+            saldo_final: (1000 + neto) as u32,
+            neto,
+        }
+    }
+
+    #[test]
+    fn test_tendencias() {
+        let vegas = ReglasMesa::default();
+        let europea = PerfilMesa::Europea.reglas();
+        let rondas = vec![
+            ronda(1, &vegas, [10, 6], 10, Resultado::Pierde, -10),
+            ronda(2, &vegas, [1, 13], 1, Resultado::Blackjack, 15),
+            // Partida nueva en otra mesa
+            ronda(1, &europea, [2, 2], 6, Resultado::Gana, 10),
+            ronda(2, &europea, [1, 6], 6, Resultado::Empate, 0),
+        ];
+        let tendencias = Tendencias::nuevas(&rondas);
+
+        assert_eq!(
+            tendencias.acumulado,
+            vec![(1.0, -10.0), (2.0, 5.0), (3.0, 15.0), (4.0, 15.0)]
+        );
+        assert_eq!(tendencias.saldo_sesion, vec![1000, 1010, 1000]);
+        assert_eq!(
+            (
+                tendencias.total.ganadas,
+                tendencias.total.perdidas,
+                tendencias.total.empates
+            ),
+            (2, 1, 1)
+        );
+
+        // Banca con 6 dos veces, con 10 y con as una vez
+        assert_eq!(tendencias.por_carta_banca[4].manos, 2);
+        assert_eq!(tendencias.por_carta_banca[8].perdidas, 1);
+        assert_eq!(tendencias.por_carta_banca[9].blackjacks, 1);
+
+        // A-6 cuenta como 17 blando y 2-2 es el total más bajo
+        let total = |valor: u8| &tendencias.por_total[(valor - TOTAL_MINIMO) as usize];
+        assert_eq!(total(17).empates, 1);
+        assert_eq!(total(4).ganadas, 1);
+        assert_eq!(total(21).blackjacks, 1);
+        assert_eq!(total(16).perdidas, 1);
+
+        let mesas: Vec<(&str, i64)> = tendencias
+            .por_mesa
+            .iter()
+            .map(|(mesa, grupo)| (mesa.as_str(), grupo.neto))
+            .collect();
+        assert_eq!(
+            mesas,
+            vec![(vegas.nombre.as_str(), 5), (europea.nombre.as_str(), 10)]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use crate::config::ruta_historial;
+use crate::game::history::{escribir_jsonl, exportar_csv, leer_jsonl, RondaJugada};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Write};
+use std::path::PathBuf;
+
+const AYUDA: &str = "Uso: ratjack historial [opciones]
+
+Exporta el historial de manos guardado al jugar.
+
+Opciones:
+  --formato F        jsonl o csv (por defecto jsonl)
+  --salida RUTA      Fichero de destino (por defecto, la salida estándar)
+  --ruta             Muestra dónde se guarda el historial
+  -h, --ayuda        Muestra esta ayuda";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Formato {
+    Jsonl,
+    Csv,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Orden {
+    Ayuda,
+    Ruta,
+    Exportar {
+        formato: Formato,
+        salida: Option<PathBuf>,
+    },
+}
+
+fn leer_argumentos(args: &[String]) -> Result<Orden, String> {
+    let mut formato = Formato::Jsonl;
+    let mut salida = None;
+
+    let mut args = args.iter();
+    while let Some(opcion) = args.next() {
+        match opcion.as_str() {
+            "-h" | "--ayuda" => return Ok(Orden::Ayuda),
+            "--ruta" => return Ok(Orden::Ruta),
+            "--formato" | "--salida" => {}
+            _ => return Err(format!("Opción desconocida: {}\n\n{}", opcion, AYUDA)),
+        }
+        let valor = args
+            .next()
+            .ok_or_else(|| format!("Falta el valor de {}", opcion))?;
+        match opcion.as_str() {
+            "--formato" => {
+                formato = match valor.as_str() {
+                    "jsonl" => Formato::Jsonl,
+                    "csv" => Formato::Csv,
+                    _ => return Err(format!("Formato desconocido: {}", valor)),
+                }
+            }
+            "--salida" => salida = Some(PathBuf::from(valor)),
+            _ => unreachable!(),
+        }
+    }
+    Ok(Orden::Exportar { formato, salida })
+}
+
+// Añade la ronda al final del historial; nunca se reescriben las anteriores
+pub fn anotar(ronda: &RondaJugada) -> Result<(), String> {
+    let ruta = ruta_historial().ok_or("No hay carpeta de datos para el historial")?;
+    if let Some(carpeta) = ruta.parent() {
+        fs::create_dir_all(carpeta).map_err(|e| e.to_string())?;
+    }
+    let mut fichero = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&ruta)
+        .map_err(|e| format!("No se pudo abrir {}: {}", ruta.display(), e))?;
+    escribir_jsonl(&mut fichero, ronda)
+}
+
+// Lee todas las rondas guardadas y cuántas líneas dañadas se saltaron; sin historial
+// todavía no hay ninguna
+pub fn cargar() -> Result<(Vec<RondaJugada>, usize), String> {
+    let Some(ruta) = ruta_historial().filter(|ruta| ruta.exists()) else {
+        return Ok((Vec::new(), 0));
+    };
+    let fichero =
+        File::open(&ruta).map_err(|e| format!("No se pudo leer {}: {}", ruta.display(), e))?;
+    leer_jsonl(BufReader::new(fichero)).map_err(|e| format!("Error en {}: {}", ruta.display(), e))
+}
+
+// Aviso de las líneas del historial que no se pudieron leer
+pub fn aviso_descartadas(descartadas: usize) -> Option<String> {
+    match descartadas {
+        0 => None,
+        1 => Some("Se saltó 1 línea dañada del historial".to_string()),
+        n => Some(format!("Se saltaron {} líneas dañadas del historial", n)),
+    }
+}
+
+// Punto de entrada de `ratjack historial`
+pub fn ejecutar(args: &[String]) -> Result<(), String> {
+    let (formato, salida) = match leer_argumentos(args)? {
+        Orden::Ayuda => {
+            println!("{}", AYUDA);
+            return Ok(());
+        }
+        Orden::Ruta => {
+            let ruta = ruta_historial().ok_or("No hay carpeta de datos para el historial")?;
+            println!("{}", ruta.display());
+            return Ok(());
+        }
+        Orden::Exportar { formato, salida } => (formato, salida),
+    };
+
+    let (rondas, descartadas) = cargar()?;
+    if let Some(aviso) = aviso_descartadas(descartadas) {
+        eprintln!("{}", aviso);
+    }
+
+    let mut destino: Box<dyn Write> = match &salida {
+        Some(ruta) => Box::new(
+            File::create(ruta)
+                .map_err(|e| format!("No se pudo crear {}: {}", ruta.display(), e))?,
+        ),
+        None => Box::new(io::stdout().lock()),
+    };
+    match formato {
+        Formato::Jsonl => rondas
+            .iter()
+            .try_for_each(|ronda| escribir_jsonl(&mut destino, ronda))?,
+        Formato::Csv => exportar_csv(&rondas, &mut destino)?,
+    }
+    destino.flush().map_err(|e| e.to_string())?;
+
+    if let Some(ruta) = salida {
+        println!("{} rondas exportadas a {}", rondas.len(), ruta.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(texto: &str) -> Vec<String> {
+        texto.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_leer_argumentos() {
+        assert_eq!(
+            leer_argumentos(&args("")),
+            Ok(Orden::Exportar {
+                formato: Formato::Jsonl,
+                salida: None,
+            })
+        );
+        assert_eq!(
+            leer_argumentos(&args("--formato csv --salida manos.csv")),
+            Ok(Orden::Exportar {
+                formato: Formato::Csv,
+                salida: Some(PathBuf::from("manos.csv")),
+            })
+        );
+        assert_eq!(leer_argumentos(&args("--ruta")), Ok(Orden::Ruta));
+        assert!(leer_argumentos(&args("--formato xml")).is_err());
+        assert!(leer_argumentos(&args("--salida")).is_err());
+        assert!(leer_argumentos(&args("--todo")).is_err());
+    }
+}
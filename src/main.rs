@@ -1,18 +1,61 @@
+mod config;
 mod game;
+mod historial;
+mod partida;
+mod sim;
 mod ui;
 
 use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use game::deck::crear_baraja;
-use game::logic::repartir_cartas;
 use game::player::Jugador;
+use game::shoe::Shoe;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::io::{self, stdout};
 
 fn main() -> io::Result<()> {
+    // Reglas de la mesa, índices y perfiles, antes de tomar el control de la terminal para poder mostrar errores
+    let cargado = config::cargar_config().and_then(|ajustes| {
+        let indices = config::cargar_indices()?;
+        Ok((ajustes, indices, config::cargar_perfiles()?))
+    });
+    let (config::Ajustes { reglas, semilla }, indices, perfiles) = match cargado {
+        Ok(cargado) => cargado,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    // `ratjack sim` simula sin interfaz y termina
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "sim") {
+        if let Err(error) = sim::ejecutar(&args[1..], &reglas, &indices) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // `ratjack historial` exporta las manos jugadas
+    if args.first().is_some_and(|arg| arg == "historial") {
+        if let Err(error) = historial::ejecutar(&args[1..]) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // La semilla de la línea de comandos tiene prioridad sobre la de la configuración
+    let semilla = match leer_semilla(&args) {
+        Ok(argumento) => argumento.or(semilla),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
     // Configuración de terminal
     enable_raw_mode()?;
     execute!(stdout(), EnterAlternateScreen)?;
@@ -22,15 +65,25 @@ fn main() -> io::Result<()> {
     terminal.clear()?;
 
     // Inicialización del juego
-    let mut baraja = crear_baraja();
+    let mut shoe = match semilla {
+        Some(semilla) => {
+            Shoe::con_semilla(reglas.mazos, reglas.penetracion, reglas.tipo_shoe, semilla)
+        }
+        None => Shoe::nuevo(reglas.mazos, reglas.penetracion, reglas.tipo_shoe),
+    };
     let mut jugador = Jugador::nuevo();
     let mut banca = Jugador::nuevo();
 
-    // Repartir cartas iniciales
-    repartir_cartas(&mut jugador, &mut banca, &mut baraja);
-
     // Ejecutar juego con la UI de ratatui
-    let result = ui::run_game(&mut terminal, &mut jugador, &mut banca, &mut baraja);
+    let result = ui::run_game(
+        &mut terminal,
+        &mut jugador,
+        &mut banca,
+        &mut shoe,
+        &reglas,
+        &indices,
+        perfiles,
+    );
 
     // Restaurar terminal
     disable_raw_mode()?;
@@ -38,3 +91,18 @@ fn main() -> io::Result<()> {
 
     result
 }
+
+// Opciones de la partida: `ratjack [--semilla N]`
+fn leer_semilla(args: &[String]) -> Result<Option<u64>, String> {
+    match args {
+        [] => Ok(None),
+        [opcion, valor] if opcion == "--semilla" => valor
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Semilla no válida: {}", valor)),
+        _ => Err(
+            "Uso: ratjack [--semilla N] | ratjack sim [opciones] | ratjack historial [opciones]"
+                .to_string(),
+        ),
+    }
+}
@@ -0,0 +1,267 @@
+use crate::config::ruta_partida;
+use crate::game::player::Jugador;
+use crate::game::rules::ReglasMesa;
+use crate::game::shoe::Shoe;
+use crate::ui::AppState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// Versión del formato de la partida guardada. Las reglas y el estado de la interfaz nuevos
+// llevan valores por defecto. Las manos, los jugadores y el shoe no: un campo nuevo en
+// ellos, o un cambio en el significado de cualquiera, obliga a subirla y a convertir las
+// partidas de versiones anteriores al leerlas
+pub const VERSION: u32 = 1;
+
+// Lo que se escribe al salir, tomado prestado del juego en curso
+#[derive(Serialize)]
+struct Guardar<'a> {
+    version: u32,
+    reglas: &'a ReglasMesa,
+    perfil: Option<&'a str>,
+    jugador: &'a Jugador,
+    banca: &'a Jugador,
+    shoe: &'a Shoe,
+    app: &'a AppState,
+}
+
+// Partida a medias tal como se leyó del fichero
+#[derive(Deserialize)]
+pub struct PartidaGuardada {
+    // Mesa de la partida, que puede no ser la de la configuración actual
+    pub reglas: ReglasMesa,
+    // Nombre del perfil con el que se jugaba
+    pub perfil: Option<String>,
+    pub jugador: Jugador,
+    pub banca: Jugador,
+    pub shoe: Shoe,
+    pub app: AppState,
+}
+
+impl PartidaGuardada {
+    // Línea que se muestra en la pantalla de inicio
+    pub fn resumen(&self) -> String {
+        format!(
+            "{} · {} rondas · saldo {} · {}",
+            self.jugador.nombre, self.app.rondas, self.jugador.saldo, self.reglas.nombre
+        )
+    }
+}
+
+pub fn a_json(
+    app: &AppState,
+    jugador: &Jugador,
+    banca: &Jugador,
+    shoe: &Shoe,
+    reglas: &ReglasMesa,
+) -> Result<String, String> {
+    let partida = Guardar {
+        version: VERSION,
+        reglas,
+        perfil: app
+            .perfil
+            .and_then(|perfil| app.perfiles.perfiles.get(perfil))
+            .map(|perfil| perfil.nombre.as_str()),
+        jugador,
+        banca,
+        shoe,
+        app,
+    };
+    serde_json::to_string(&partida).map_err(|e| e.to_string())
+}
+
+// Lee una partida de cualquier versión igual o anterior a la actual
+pub fn desde_json(texto: &str) -> Result<PartidaGuardada, String> {
+    let valor: serde_json::Value = serde_json::from_str(texto).map_err(|e| e.to_string())?;
+    let version = valor
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or("La partida guardada no indica su versión")?;
+    if version > VERSION as u64 {
+        return Err(format!(
+            "La partida se guardó con una versión más nueva de RatJack (formato {})",
+            version
+        ));
+    }
+    let mut partida: PartidaGuardada = serde_json::from_value(valor).map_err(|e| e.to_string())?;
+    // La mesa guardada pasa a ser la de la partida: se comprueba como la de la configuración
+    partida.reglas.validar()?;
+    // Lo que se editó a mano y haría fallar el juego se rechaza antes de usarlo
+    partida
+        .jugador
+        .validar()
+        .map_err(|e| format!("El jugador {}", e))?;
+    partida
+        .banca
+        .validar()
+        .map_err(|e| format!("La banca {}", e))?;
+    partida.shoe.validar()?;
+    partida.shoe.recontar();
+    Ok(partida)
+}
+
+pub fn guardar(
+    app: &AppState,
+    jugador: &Jugador,
+    banca: &Jugador,
+    shoe: &Shoe,
+    reglas: &ReglasMesa,
+) -> Result<(), String> {
+    let ruta = ruta_partida().ok_or("No hay carpeta de datos para guardar la partida")?;
+    if let Some(carpeta) = ruta.parent() {
+        fs::create_dir_all(carpeta).map_err(|e| e.to_string())?;
+    }
+    fs::write(&ruta, a_json(app, jugador, banca, shoe, reglas)?)
+        .map_err(|e| format!("No se pudo guardar {}: {}", ruta.display(), e))
+}
+
+// Partida guardada la última vez que se salió, si la hay
+pub fn cargar() -> Result<Option<PartidaGuardada>, String> {
+    let Some(ruta) = ruta_partida().filter(|ruta| ruta.exists()) else {
+        return Ok(None);
+    };
+    let texto = fs::read_to_string(&ruta)
+        .map_err(|e| format!("No se pudo leer {}: {}", ruta.display(), e))?;
+    desde_json(&texto)
+        .map(Some)
+        .map_err(|e| format!("Error en {}: {}", ruta.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::counting::SistemaConteo;
+    use crate::game::logic::{apostar, repartir_cartas};
+    use crate::game::profile::Perfiles;
+    use crate::game::rules::TipoShoe;
+    use crate::ui::GameState;
+
+    #[test]
+    fn test_partida_ida_y_vuelta() {
+        let reglas = ReglasMesa::default();
+        let mut shoe = Shoe::con_semilla(6, 0.75, TipoShoe::CartaCorte, 42);
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        assert!(apostar(&mut jugador, 50, &reglas));
+        repartir_cartas(&mut jugador, &mut banca, &mut shoe, &reglas);
+
+        let mut app = AppState::new();
+        app.estado = GameState::TurnoJugador;
+        app.apuesta = 50;
+        app.rondas = 7;
+        app.perfiles = Perfiles::default();
+        app.perfiles.crear("Ana").unwrap();
+        app.perfil = Some(0);
+
+        let texto = a_json(&app, &jugador, &banca, &shoe, &reglas).unwrap();
+        let mut partida = desde_json(&texto).unwrap();
+
+        assert_eq!(partida.perfil.as_deref(), Some("Ana"));
+        assert_eq!(partida.reglas, reglas);
+        assert!(matches!(partida.app.estado, GameState::TurnoJugador));
+        assert_eq!((partida.app.apuesta, partida.app.rondas), (50, 7));
+        assert_eq!(partida.jugador.saldo, jugador.saldo);
+        assert_eq!(partida.jugador.mano().cartas, jugador.mano().cartas);
+        assert_eq!(partida.banca.mano().cartas, banca.mano().cartas);
+
+        // El shoe sigue con las mismas cartas, la misma cuenta y el mismo generador
+        assert_eq!(partida.shoe.restantes(), shoe.restantes());
+        assert_eq!(
+            partida.shoe.cuenta(SistemaConteo::HiLo, 6),
+            shoe.cuenta(SistemaConteo::HiLo, 6)
+        );
+        partida.shoe.barajar();
+        shoe.barajar();
+        let siguientes = |shoe: &mut Shoe| {
+            std::iter::from_fn(|| shoe.robar())
+                .take(10)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(siguientes(&mut partida.shoe), siguientes(&mut shoe));
+    }
+
+    #[test]
+    fn test_versiones() {
+        let reglas = ReglasMesa::default();
+        let shoe = Shoe::con_semilla(1, 0.75, TipoShoe::CartaCorte, 1);
+        let texto = a_json(
+            &AppState::new(),
+            &Jugador::nuevo(),
+            &Jugador::nuevo(),
+            &shoe,
+            &reglas,
+        )
+        .unwrap();
+        let mut valor: serde_json::Value = serde_json::from_str(&texto).unwrap();
+
+        // Lo que falta, como las reglas añadidas después, toma su valor por defecto
+        valor["reglas"] = serde_json::json!({ "mazos": 2 });
+        valor["app"].as_object_mut().unwrap().remove("conteo");
+        let partida = desde_json(&valor.to_string()).unwrap();
+        assert_eq!(partida.reglas.mazos, 2);
+        assert_eq!(partida.reglas.apuesta_minima, reglas.apuesta_minima);
+
+        // Una mesa imposible, editada a mano, no se carga
+        let mut invalida = valor.clone();
+        invalida["reglas"] = serde_json::json!({ "mazos": 0 });
+        assert!(desde_json(&invalida.to_string()).is_err());
+        invalida["reglas"] = serde_json::json!({ "apuesta_minima": 500, "apuesta_maxima": 100 });
+        assert!(desde_json(&invalida.to_string()).is_err());
+
+        valor["version"] = serde_json::json!(VERSION + 1);
+        assert!(desde_json(&valor.to_string()).is_err());
+        assert!(desde_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_partida_danada() {
+        let reglas = ReglasMesa::default();
+        let mut shoe = Shoe::con_semilla(1, 0.75, TipoShoe::CartaCorte, 3);
+        let mut jugador = Jugador::nuevo();
+        let mut banca = Jugador::nuevo();
+        assert!(apostar(&mut jugador, 50, &reglas));
+        repartir_cartas(&mut jugador, &mut banca, &mut shoe, &reglas);
+        let texto = a_json(&AppState::new(), &jugador, &banca, &shoe, &reglas).unwrap();
+        let valor: serde_json::Value = serde_json::from_str(&texto).unwrap();
+        assert!(desde_json(&texto).is_ok());
+
+        // Cada cambio haría fallar el juego más tarde, así que la partida no se carga
+        let danada = |cambiar: &dyn Fn(&mut serde_json::Value)| {
+            let mut valor = valor.clone();
+            cambiar(&mut valor);
+            desde_json(&valor.to_string()).err()
+        };
+        let sin_manos = danada(&|v| v["jugador"]["manos"] = serde_json::json!([]));
+        assert_eq!(
+            sin_manos.as_deref(),
+            Some("El jugador no tiene ninguna mano")
+        );
+        let banca_sin_manos = danada(&|v| v["banca"]["manos"] = serde_json::json!([]));
+        assert_eq!(
+            banca_sin_manos.as_deref(),
+            Some("La banca no tiene ninguna mano")
+        );
+        let activa = danada(&|v| v["jugador"]["mano_activa"] = serde_json::json!(1));
+        assert_eq!(
+            activa.as_deref(),
+            Some("El jugador tiene como activa una mano que no existe")
+        );
+        let en_mano = danada(&|v| v["jugador"]["manos"][0]["cartas"][0]["valor"] = 0.into());
+        assert_eq!(
+            en_mano.as_deref(),
+            Some("El jugador tiene una carta que no existe")
+        );
+        assert!(danada(&|v| v["banca"]["manos"][0]["cartas"][1]["valor"] = 14.into()).is_some());
+        let en_shoe = danada(&|v| v["shoe"]["cartas"][0]["valor"] = 14.into());
+        assert_eq!(
+            en_shoe.as_deref(),
+            Some("El shoe tiene una carta que no existe")
+        );
+        assert!(danada(&|v| v["shoe"]["repartidas"][0]["valor"] = 0.into()).is_some());
+        let posicion = danada(&|v| v["shoe"]["total"] = 10.into());
+        assert_eq!(
+            posicion.as_deref(),
+            Some("La posición del shoe no cuadra con sus cartas")
+        );
+        assert!(danada(&|v| v["shoe"]["total"] = 0.into()).is_some());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::game::deviations::TablaIndices;
+use crate::game::rules::ReglasMesa;
+use crate::game::simulation::{
+    simular, Estadisticas, EstrategiaBasica, EstrategiaIndices, ImitarBanca, Simulacion,
+};
+use std::thread;
+use std::time::Instant;
+
+const AYUDA: &str = "Uso: ratjack sim [opciones]
+
+Simula rondas sin interfaz con las reglas de la configuración.
+
+Opciones:
+  --rondas N         Rondas a simular (por defecto 1000000)
+  --hilos N          Hilos de trabajo (por defecto, uno por núcleo)
+  --semilla N        Semilla para repetir la simulación (por defecto, aleatoria)
+  --estrategia E     basica, indices o banca (por defecto basica)
+  -h, --ayuda        Muestra esta ayuda";
+
+// Estrategias disponibles desde la línea de comandos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TipoEstrategia {
+    Basica,
+    Indices,
+    Banca,
+}
+
+impl TipoEstrategia {
+    fn nombre(&self) -> &'static str {
+        match self {
+            TipoEstrategia::Basica => "estrategia básica",
+            TipoEstrategia::Indices => "estrategia básica con índices",
+            TipoEstrategia::Banca => "imitar a la banca",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Argumentos {
+    simulacion: Simulacion,
+    estrategia: TipoEstrategia,
+}
+
+// Lee las opciones de `ratjack sim`. Devuelve None si se pide la ayuda
+fn leer_argumentos(args: &[String]) -> Result<Option<Argumentos>, String> {
+    let mut argumentos = Argumentos {
+        simulacion: Simulacion {
+            rondas: 1_000_000,
+            hilos: thread::available_parallelism().map_or(1, |n| n.get()),
+            semilla: rand::random(),
+        },
+        estrategia: TipoEstrategia::Basica,
+    };
+
+    let mut args = args.iter();
+    while let Some(opcion) = args.next() {
+        match opcion.as_str() {
+            "-h" | "--ayuda" => return Ok(None),
+            "--rondas" | "--hilos" | "--semilla" | "--estrategia" => {}
+            _ => return Err(format!("Opción desconocida: {}\n\n{}", opcion, AYUDA)),
+        }
+        let valor = args
+            .next()
+            .ok_or_else(|| format!("Falta el valor de {}", opcion))?;
+        let numero = || {
+            valor
+                .parse::<u64>()
+                .map_err(|_| format!("Valor no válido para {}: {}", opcion, valor))
+        };
+        match opcion.as_str() {
+            "--rondas" => argumentos.simulacion.rondas = numero()?,
+            "--hilos" => argumentos.simulacion.hilos = numero()?.max(1) as usize,
+            "--semilla" => argumentos.simulacion.semilla = numero()?,
+            "--estrategia" => {
+                argumentos.estrategia = match valor.as_str() {
+                    "basica" => TipoEstrategia::Basica,
+                    "indices" => TipoEstrategia::Indices,
+                    "banca" => TipoEstrategia::Banca,
+                    _ => return Err(format!("Estrategia desconocida: {}", valor)),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(Some(argumentos))
+}
+
+// Punto de entrada de `ratjack sim`
+pub fn ejecutar(
+    args: &[String],
+    reglas: &ReglasMesa,
+    indices: &TablaIndices,
+) -> Result<(), String> {
+    let Some(Argumentos {
+        simulacion,
+        estrategia,
+    }) = leer_argumentos(args)?
+    else {
+        println!("{}", AYUDA);
+        return Ok(());
+    };
+
+    println!(
+        "{} | {} mazos | {}",
+        reglas.nombre,
+        reglas.mazos,
+        estrategia.nombre()
+    );
+    println!(
+        "Simulando {} rondas en {} hilos (semilla {})...",
+        simulacion.rondas, simulacion.hilos, simulacion.semilla
+    );
+
+    let inicio = Instant::now();
+    let estadisticas = match estrategia {
+        TipoEstrategia::Basica => simular(reglas, simulacion, || EstrategiaBasica),
+        TipoEstrategia::Indices => simular(reglas, simulacion, || EstrategiaIndices {
+            tabla: indices.clone(),
+        }),
+        TipoEstrategia::Banca => simular(reglas, simulacion, || ImitarBanca),
+    };
+    println!("Terminado en {:.1} s\n", inicio.elapsed().as_secs_f64());
+    imprimir_informe(&estadisticas);
+    Ok(())
+}
+
+fn imprimir_informe(estadisticas: &Estadisticas) {
+    let porcentaje = |valor: f64| format!("{:.3}%", valor * 100.0);
+    let (inferior, superior) = estadisticas.intervalo(1.96);
+
+    println!(
+        "Ventaja de la casa:  {} ± {}",
+        porcentaje(estadisticas.ventaja_casa()),
+        porcentaje(1.96 * estadisticas.error_estandar())
+    );
+    println!(
+        "IC del 95%:          {} a {}",
+        porcentaje(inferior),
+        porcentaje(superior)
+    );
+    println!(
+        "Desviación típica:   {:.3} apuestas por mano inicial",
+        estadisticas.desviacion()
+    );
+    println!(
+        "Manos:               {} ({:.3} por ronda)",
+        estadisticas.manos,
+        estadisticas.manos as f64 / estadisticas.rondas.max(1) as f64
+    );
+    println!(
+        "Ganadas:             {}",
+        porcentaje(estadisticas.tasa(estadisticas.ganadas))
+    );
+    println!(
+        "Perdidas:            {}",
+        porcentaje(estadisticas.tasa(estadisticas.perdidas))
+    );
+    println!(
+        "Empates:             {}",
+        porcentaje(estadisticas.tasa(estadisticas.empates))
+    );
+    println!(
+        "Blackjacks:          {} de las rondas",
+        porcentaje(estadisticas.frecuencia_blackjack())
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(texto: &str) -> Vec<String> {
+        texto.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_leer_argumentos() {
+        let argumentos = leer_argumentos(&args(
+            "--rondas 500 --hilos 3 --semilla 9 --estrategia indices",
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            argumentos.simulacion,
+            Simulacion {
+                rondas: 500,
+                hilos: 3,
+                semilla: 9,
+            }
+        );
+        assert_eq!(argumentos.estrategia, TipoEstrategia::Indices);
+
+        assert_eq!(leer_argumentos(&args("--ayuda")), Ok(None));
+        assert!(leer_argumentos(&args("--rondas")).is_err());
+        assert!(leer_argumentos(&args("--rondas muchas")).is_err());
+        assert!(leer_argumentos(&args("--estrategia martingala")).is_err());
+        assert!(leer_argumentos(&args("--turbo 1")).is_err());
+    }
+}
@@ -1,39 +1,153 @@
+use crate::config;
 use crate::game::{
+    chart::{self, Casilla, CursorTabla, CARTAS_BANCA},
+    counting::{cuenta_indices, cuenta_real, PuntuacionConteo, SistemaConteo},
     deck::Carta,
-    logic::{determinar_ganador, jugar_turno, repartir_cartas},
+    deviations::TablaIndices,
+    drill::Entrenamiento,
+    ev::{Composicion, EvaluadorExacto},
+    hand::Mano,
+    history::RondaJugada,
+    logic::{
+        apostar, avanzar_mano, banca_puede_tener_blackjack, determinar_ganador, dinero_par,
+        dividir, doblar, jugar_banca, jugar_turno, mensaje_seguro, ofrecer_seguro, puede_pedir,
+        puede_rendirse, reiniciar_partida, rendirse, repartir_cartas, resolver_seguro,
+        revisar_blackjack, tomar_seguro, Liquidacion,
+    },
+    mistakes::{Categoria, RegistroDecisiones},
     player::Jugador,
+    profile::{EstadisticasPerfil, Perfiles},
+    replay::Repeticion,
+    rules::{ReglaBanca, ReglasMesa, TipoShoe},
+    shoe::Shoe,
+    strategy::{decision_actual, recomendar, Accion, Opciones, Situacion},
+    trends::{Tendencias, TOTAL_MINIMO},
 };
+use crate::historial;
+use crate::partida::{self, PartidaGuardada};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, BorderType, Borders, Paragraph},
+    symbols,
+    text::{Line, Span},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, BorderType, Borders, Cell, Chart, Dataset, GraphType,
+        LineGauge, Paragraph, Row, Sparkline, Table,
+    },
     Terminal,
 };
+use serde::{Deserialize, Serialize};
 use std::io;
 
+// Valor de la ficha con la que se sube o baja la apuesta
+const FICHA: u32 = 10;
+
+// Cada cuántas rondas se pregunta la cuenta en modo examen
+const RONDAS_EXAMEN: u32 = 3;
+
+// Qué se muestra del conteo de cartas
+#[derive(Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModoConteo {
+    #[default]
+    Desactivado,
+    Corriente,
+    Real,
+    // Cuenta oculta con preguntas periódicas
+    Examen,
+}
+
+impl ModoConteo {
+    fn siguiente(&self) -> ModoConteo {
+        match self {
+            ModoConteo::Desactivado => ModoConteo::Corriente,
+            ModoConteo::Corriente => ModoConteo::Real,
+            ModoConteo::Real => ModoConteo::Examen,
+            ModoConteo::Examen => ModoConteo::Desactivado,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
 pub enum GameState {
+    #[default]
     Inicio,
+    NuevoPerfil,
+    Apuesta,
+    DecisionPrevia,
     TurnoJugador,
     TurnoBanca,
     FinJuego,
+    Informe,
+    Examen,
+    Repeticion,
+    Estadisticas,
+    TablaEstrategia,
+    Practica,
 }
 
-impl Default for GameState {
-    fn default() -> Self {
-        GameState::Inicio
-    }
-}
+// Situación, ronda, cartas repartidas y mano activa: identifican una decisión del jugador
+type ClaveEv = (Situacion, u32, usize, usize);
 
-#[derive(Default)]
+// Carta visible, ronda, cartas repartidas y si se ve la oculta: identifican lo que se
+// sabe de la banca
+type ClaveBanca = (u8, u32, usize, bool);
+
+// Se guarda al salir, salvo lo que se recalcula o depende de los ficheros actuales
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppState {
     pub estado: GameState,
     pub mensaje: String,
     pub opciones: Vec<String>,
     pub seleccion: usize,
     pub mostrar_todas_cartas_banca: bool,
+    pub apuesta: u32,
+    pub mostrar_consejo: bool,
+    pub mostrar_ev: bool,
+    pub mostrar_banca: bool,
+    // EV exacto de cada jugada, guardado con la decisión para la que se calculó
+    #[serde(skip)]
+    pub ev: Option<(ClaveEv, Vec<(Accion, f64)>)>,
+    // Probabilidad de cada final de la banca, guardada con lo que se sabía al calcularla
+    #[serde(skip)]
+    pub finales_banca: Option<(ClaveBanca, [f64; 7])>,
+    pub registro: RegistroDecisiones,
+    // Ronda en curso, que se guarda en el historial de manos al liquidarse
+    pub ronda: Option<RondaJugada>,
+    // Rondas del historial que se están repasando
+    #[serde(skip)]
+    pub repeticion: Option<Repeticion>,
+    // Resultados del historial agrupados para el panel de estadísticas
+    #[serde(skip)]
+    pub tendencias: Option<Tendencias>,
+    // Casilla elegida en la tabla de estrategia básica
+    #[serde(skip)]
+    pub cursor_tabla: CursorTabla,
+    // Preguntas del modo práctica
+    #[serde(skip)]
+    pub practica: Option<Entrenamiento>,
+    // Estados a los que se vuelve al cerrar la repetición, las estadísticas, la tabla o la práctica
+    #[serde(skip)]
+    pub estado_previo: Vec<(GameState, String)>,
+    // El informe se muestra antes de salir del juego
+    #[serde(skip)]
+    pub saliendo: bool,
+    pub conteo: ModoConteo,
+    pub sistema: SistemaConteo,
+    pub puntuacion: PuntuacionConteo,
+    // Respuesta que se está escribiendo en el examen de conteo
+    pub respuesta: String,
+    pub rondas: u32,
+    // Perfiles guardados y el elegido al empezar, si se juega con uno
+    #[serde(skip)]
+    pub perfiles: Perfiles,
+    #[serde(skip)]
+    pub perfil: Option<usize>,
+    // Resumen de la partida guardada que se puede continuar desde el inicio
+    #[serde(skip)]
+    pub partida: Option<String>,
 }
 
 impl AppState {
@@ -41,17 +155,65 @@ impl AppState {
         Self {
             estado: GameState::Inicio,
             mensaje: String::from("¡Bienvenido a RatJack!"),
-            opciones: vec![String::from("Pedir carta"), String::from("Plantarse")],
+            opciones: vec![
+                String::from("Pedir carta"),
+                String::from("Plantarse"),
+                String::from("Doblar"),
+                String::from("Dividir"),
+            ],
             seleccion: 0,
             mostrar_todas_cartas_banca: false,
+            apuesta: 0,
+            mostrar_consejo: false,
+            mostrar_ev: false,
+            mostrar_banca: false,
+            ev: None,
+            finales_banca: None,
+            registro: RegistroDecisiones::default(),
+            ronda: None,
+            repeticion: None,
+            tendencias: None,
+            cursor_tabla: CursorTabla::default(),
+            practica: None,
+            estado_previo: Vec::new(),
+            saliendo: false,
+            conteo: ModoConteo::Desactivado,
+            sistema: SistemaConteo::HiLo,
+            puntuacion: PuntuacionConteo::default(),
+            respuesta: String::new(),
+            rondas: 0,
+            perfiles: Perfiles::default(),
+            perfil: None,
+            partida: None,
         }
     }
 
     // Método para actualizar las opciones según el estado del juego
     pub fn actualizar_opciones(&mut self) {
         match self.estado {
+            GameState::DecisionPrevia => {
+                self.opciones = vec![
+                    String::from("Seguro"),
+                    String::from("Seguir"),
+                    String::from("Rendirse"),
+                ];
+                self.mostrar_todas_cartas_banca = false;
+            }
+            GameState::Apuesta => {
+                self.opciones = vec![
+                    String::from("Subir apuesta"),
+                    String::from("Bajar apuesta"),
+                    String::from("Repartir"),
+                ];
+                self.mostrar_todas_cartas_banca = false;
+            }
             GameState::TurnoJugador => {
-                self.opciones = vec![String::from("Pedir carta"), String::from("Plantarse")];
+                self.opciones = vec![
+                    String::from("Pedir carta"),
+                    String::from("Plantarse"),
+                    String::from("Doblar"),
+                    String::from("Dividir"),
+                ];
                 self.mostrar_todas_cartas_banca = false;
             }
             GameState::FinJuego => {
@@ -68,79 +230,516 @@ pub fn run_game<B: Backend>(
     terminal: &mut Terminal<B>,
     jugador: &mut Jugador,
     banca: &mut Jugador,
-    baraja: &mut Vec<Carta>,
+    shoe: &mut Shoe,
+    reglas: &ReglasMesa,
+    indices: &TablaIndices,
+    perfiles: Perfiles,
 ) -> io::Result<()> {
     let mut app = AppState::new();
+    app.apuesta = reglas.apuesta_minima;
+    let mut guardada = partida::cargar().unwrap_or_else(|error| {
+        app.mensaje = format!("No se pudo recuperar la partida guardada. {}", error);
+        None
+    });
+    app.partida = guardada.as_ref().map(PartidaGuardada::resumen);
+    // Se propone continuar la partida guardada, luego el último perfil usado o jugar sin perfil
+    app.seleccion = if app.partida.is_some() {
+        0
+    } else {
+        perfiles
+            .ultimo
+            .as_deref()
+            .and_then(|nombre| perfiles.buscar(nombre))
+            .unwrap_or(perfiles.perfiles.len() + 1)
+    };
+    app.perfiles = perfiles;
+    // Al continuar una partida se juega con las reglas con las que empezó
+    let mut mesa = reglas.clone();
 
     loop {
-        terminal.draw(|frame| render_ui(frame, jugador, banca, &app))?;
+        let reglas = &mesa;
+        if app.mostrar_ev {
+            actualizar_ev(&mut app, jugador, banca, shoe, reglas);
+        }
+        if app.mostrar_banca {
+            actualizar_finales_banca(&mut app, jugador, banca, shoe, reglas);
+        }
+        terminal.draw(|frame| render_ui(frame, jugador, banca, shoe, &app, reglas, indices))?;
 
         if let Event::Key(key) = event::read()? {
             if key.kind == KeyEventKind::Press {
+                // Al escribir el nombre de un perfil ninguna tecla es un atajo
+                if let GameState::NuevoPerfil = app.estado {
+                    match key.code {
+                        KeyCode::Char(c) if app.respuesta.chars().count() < 20 => {
+                            app.respuesta.push(c)
+                        }
+                        KeyCode::Backspace => {
+                            app.respuesta.pop();
+                        }
+                        KeyCode::Enter => match app.perfiles.crear(&app.respuesta) {
+                            Ok(perfil) => elegir_perfil(&mut app, jugador, reglas, Some(perfil)),
+                            Err(error) => app.mensaje = error,
+                        },
+                        KeyCode::Esc => {
+                            app.estado = GameState::Inicio;
+                            app.mensaje = String::from("¡Bienvenido a RatJack!");
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Las estadísticas se abren desde cualquier estado y se vuelve a él al cerrarlas
+                if key.code == KeyCode::Char('g') {
+                    if let GameState::Estadisticas = app.estado {
+                        volver(&mut app);
+                    } else {
+                        abrir_estadisticas(&mut app);
+                    }
+                    continue;
+                }
+                if key.code == KeyCode::Char('t') {
+                    if let GameState::TablaEstrategia = app.estado {
+                        volver(&mut app);
+                    } else {
+                        let previo = std::mem::replace(&mut app.estado, GameState::TablaEstrategia);
+                        app.estado_previo.push((previo, app.mensaje.clone()));
+                        app.mensaje = format!("Estrategia básica de la mesa {}", reglas.nombre);
+                    }
+                    continue;
+                }
+                if key.code == KeyCode::Char('a') {
+                    if let GameState::Practica = app.estado {
+                        volver(&mut app);
+                    } else {
+                        abrir_practica(&mut app);
+                    }
+                    continue;
+                }
+
+                // El consejo de estrategia básica se puede activar en cualquier momento
+                if key.code == KeyCode::Char('h') && !matches!(app.estado, GameState::TurnoBanca) {
+                    app.mostrar_consejo = !app.mostrar_consejo;
+                    continue;
+                }
+                if key.code == KeyCode::Char('x') && !matches!(app.estado, GameState::TurnoBanca) {
+                    app.mostrar_ev = !app.mostrar_ev;
+                    continue;
+                }
+                if key.code == KeyCode::Char('b') && !matches!(app.estado, GameState::TurnoBanca) {
+                    app.mostrar_banca = !app.mostrar_banca;
+                    continue;
+                }
+
+                // Modo de conteo y sistema, fuera de los exámenes
+                let cambia_conteo = !matches!(
+                    app.estado,
+                    GameState::TurnoBanca
+                        | GameState::Informe
+                        | GameState::Examen
+                        | GameState::Repeticion
+                        | GameState::Estadisticas
+                        | GameState::TablaEstrategia
+                        | GameState::Practica
+                );
+                if cambia_conteo && key.code == KeyCode::Char('c') {
+                    app.conteo = app.conteo.siguiente();
+                    continue;
+                }
+                if cambia_conteo && key.code == KeyCode::Char('k') {
+                    app.sistema = app.sistema.siguiente();
+                    app.mensaje = format!("Sistema de conteo: {}", app.sistema.nombre());
+                    continue;
+                }
+
+                // Al salir se guarda la partida y se muestra el informe de decisiones, si hay alguna
+                if key.code == KeyCode::Char('q') && !matches!(app.estado, GameState::Informe) {
+                    while let GameState::Repeticion
+                    | GameState::Estadisticas
+                    | GameState::TablaEstrategia
+                    | GameState::Practica = app.estado
+                    {
+                        if !volver(&mut app) {
+                            break;
+                        }
+                    }
+                    // Sin haber empezado a jugar se conserva la partida guardada anterior.
+                    // Si no se puede guardar, se avisa sin perder los perfiles ni el informe
+                    let guardada = if matches!(app.estado, GameState::Inicio) {
+                        Ok(())
+                    } else {
+                        partida::guardar(&app, jugador, banca, shoe, reglas)
+                    };
+                    if let Err(error) = &guardada {
+                        app.mensaje = error.clone();
+                    }
+                    if app.registro.decisiones.is_empty() {
+                        salir(&app)?;
+                        return guardada.map_err(io::Error::other);
+                    }
+                    app.saliendo = true;
+                    app.estado = GameState::Informe;
+                    continue;
+                }
+
+                // Fuera de una ronda se pueden repasar las rondas del historial
+                let sin_ronda = matches!(
+                    app.estado,
+                    GameState::Inicio | GameState::Apuesta | GameState::FinJuego
+                );
+                if sin_ronda && key.code == KeyCode::Char('r') {
+                    abrir_repeticion(&mut app);
+                    continue;
+                }
+
+                // Antes de actuar se anota la situación para compararla con la estrategia básica
+                let decision = decision_actual(jugador, banca, reglas, false);
+                // y lo que ya ha salido del shoe, para anotar en el historial las cartas de cada jugada
+                let antes = shoe.repartidas().len();
+                let mano = jugador.mano_activa;
+
                 match app.estado {
+                    // Elección de perfil: los guardados, uno nuevo o jugar sin perfil
                     GameState::Inicio => {
-                        if key.code == KeyCode::Enter || key.code == KeyCode::Char(' ') {
-                            app.estado = GameState::TurnoJugador;
-                            app.actualizar_opciones();
-                        }
-                        if key.code == KeyCode::Char('q') {
-                            return Ok(());
+                        // Si hay partida guardada, la primera opción es continuarla
+                        let continuar = usize::from(app.partida.is_some());
+                        let nuevo = continuar + app.perfiles.perfiles.len();
+                        match key.code {
+                            KeyCode::Up => app.seleccion = app.seleccion.saturating_sub(1),
+                            KeyCode::Down => app.seleccion = (app.seleccion + 1).min(nuevo + 1),
+                            KeyCode::Enter | KeyCode::Char(' ') if app.seleccion < continuar => {
+                                if let Some(partida) = guardada.take() {
+                                    *jugador = partida.jugador;
+                                    *banca = partida.banca;
+                                    *shoe = partida.shoe;
+                                    mesa = partida.reglas;
+                                    let perfiles = std::mem::take(&mut app.perfiles);
+                                    app = partida.app;
+                                    app.perfil =
+                                        partida.perfil.and_then(|nombre| perfiles.buscar(&nombre));
+                                    app.perfiles = perfiles;
+                                    app.mensaje = format!("Partida recuperada. {}", app.mensaje);
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') if app.seleccion == nuevo => {
+                                app.estado = GameState::NuevoPerfil;
+                                app.respuesta.clear();
+                                app.mensaje = "Nombre del nuevo perfil:".to_string();
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') => {
+                                let perfil = Some(app.seleccion - continuar)
+                                    .filter(|&i| i < nuevo - continuar);
+                                elegir_perfil(&mut app, jugador, reglas, perfil);
+                            }
+                            _ => {}
                         }
                     }
+                    GameState::NuevoPerfil => {}
+                    GameState::Apuesta => match key.code {
+                        KeyCode::Char('+') | KeyCode::Up | KeyCode::Right => {
+                            let tope = reglas.apuesta_maxima.min(jugador.saldo);
+                            app.apuesta = (app.apuesta + FICHA).min(tope);
+                            app.mensaje = format!("Apuesta: {}", app.apuesta);
+                        }
+                        KeyCode::Char('-') | KeyCode::Down | KeyCode::Left => {
+                            app.apuesta =
+                                app.apuesta.saturating_sub(FICHA).max(reglas.apuesta_minima);
+                            app.mensaje = format!("Apuesta: {}", app.apuesta);
+                        }
+                        KeyCode::Enter | KeyCode::Char(' ') => {
+                            let saldo = jugador.saldo;
+                            let posicion = shoe.total() - shoe.restantes();
+                            if apostar(jugador, app.apuesta, reglas) {
+                                repartir_cartas(jugador, banca, shoe, reglas);
+                                app.ronda = Some(RondaJugada::empezar(
+                                    app.rondas + 1,
+                                    shoe.semilla(),
+                                    reglas,
+                                    posicion,
+                                    saldo,
+                                    jugador,
+                                    banca,
+                                ));
+
+                                if hay_decision_previa(jugador, banca, reglas) {
+                                    app.estado = GameState::DecisionPrevia;
+                                    app.actualizar_opciones();
+                                    app.mensaje = if !ofrecer_seguro(banca, reglas) {
+                                        "La banca puede tener blackjack. ¿Rendición temprana?"
+                                            .to_string()
+                                    } else if jugador.mano().es_blackjack() {
+                                        "La banca muestra un as. ¿Dinero par?".to_string()
+                                    } else {
+                                        "La banca muestra un as. ¿Seguro?".to_string()
+                                    };
+                                } else {
+                                    app.mensaje = format!("Apuesta de {}. ¡Suerte!", app.apuesta);
+                                    revisar_reparto(&mut app, jugador, banca, reglas, None);
+                                }
+                            } else {
+                                app.mensaje = "Saldo insuficiente para esa apuesta".to_string();
+                            }
+                        }
+                        _ => {}
+                    },
+                    GameState::DecisionPrevia => match key.code {
+                        KeyCode::Char('s') | KeyCode::Char('1')
+                            if ofrecer_seguro(banca, reglas) =>
+                        {
+                            if jugador.mano().es_blackjack() {
+                                // Dinero par - la ronda termina al momento
+                                let liquidacion = dinero_par(jugador);
+                                app.mensaje = liquidacion
+                                    .map(|liquidacion| {
+                                        cerrar_ronda(&mut app, jugador, banca, &liquidacion)
+                                    })
+                                    .unwrap_or_default();
+                                app.estado = GameState::FinJuego;
+                                app.actualizar_opciones();
+                            } else if tomar_seguro(jugador) {
+                                if let Some(ronda) = &mut app.ronda {
+                                    ronda.seguro = jugador.seguro;
+                                }
+                                let aviso = format!("Seguro de {}.", jugador.seguro);
+                                revisar_reparto(&mut app, jugador, banca, reglas, Some(aviso));
+                            } else {
+                                app.mensaje = "Saldo insuficiente para el seguro".to_string();
+                            }
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('2') => {
+                            let aviso = if ofrecer_seguro(banca, reglas) {
+                                Some("Sin seguro.".to_string())
+                            } else {
+                                None
+                            };
+                            revisar_reparto(&mut app, jugador, banca, reglas, aviso);
+                        }
+                        KeyCode::Char('r') | KeyCode::Char('5') => {
+                            // Rendición temprana, antes de que la banca mire su carta
+                            let previa = decision_actual(jugador, banca, reglas, true);
+                            if rendirse(jugador, reglas, true) {
+                                anotar_decision(&mut app, previa, Accion::Rendirse, reglas);
+                                anotar_paso(&mut app, mano, Accion::Rendirse, shoe, antes);
+                                let aviso = "Te rindes.".to_string();
+                                revisar_reparto(&mut app, jugador, banca, reglas, Some(aviso));
+                            } else {
+                                app.mensaje = "No puedes rendirte ahora".to_string();
+                            }
+                        }
+                        _ => {}
+                    },
                     GameState::TurnoJugador => {
                         match key.code {
                             KeyCode::Enter | KeyCode::Char('1') | KeyCode::Char('p') => {
                                 // Pedir carta - atajo de teclado
-                                jugar_turno(jugador, baraja, true);
-                                jugador.puntos = jugador.puntaje();
-                                if jugador.puntos > 21 {
-                                    app.mensaje = determinar_ganador(jugador, banca);
-                                    app.estado = GameState::FinJuego;
-                                    app.actualizar_opciones();
+                                if puede_pedir(jugador) {
+                                    anotar_decision(&mut app, decision, Accion::Pedir, reglas);
+                                    jugar_turno(jugador, shoe, true);
+                                    if jugador.mano().pasada() {
+                                        avanzar_mano(jugador, shoe, reglas);
+                                    }
+                                    anotar_paso(&mut app, mano, Accion::Pedir, shoe, antes);
+                                    comprobar_turno(&mut app, jugador, banca, reglas);
+                                } else {
+                                    app.mensaje = "Esta mano no admite más cartas".to_string();
                                 }
                             }
                             KeyCode::Char('2') | KeyCode::Char('s') => {
                                 // Plantarse - atajo de teclado
-                                app.estado = GameState::TurnoBanca;
-                                app.mensaje = "Turno de la banca".to_string();
+                                anotar_decision(&mut app, decision, Accion::Plantarse, reglas);
+                                avanzar_mano(jugador, shoe, reglas);
+                                anotar_paso(&mut app, mano, Accion::Plantarse, shoe, antes);
+                                comprobar_turno(&mut app, jugador, banca, reglas);
                             }
-                            KeyCode::Char('q') => {
-                                return Ok(());
+                            KeyCode::Char('3') | KeyCode::Char('d') => {
+                                // Doblar - atajo de teclado
+                                if doblar(jugador, shoe, reglas) {
+                                    anotar_decision(&mut app, decision, Accion::Doblar, reglas);
+                                    anotar_paso(&mut app, mano, Accion::Doblar, shoe, antes);
+                                    comprobar_turno(&mut app, jugador, banca, reglas);
+                                } else {
+                                    app.mensaje = "No puedes doblar esta mano".to_string();
+                                }
+                            }
+                            KeyCode::Char('4') | KeyCode::Char('v') => {
+                                // Dividir - atajo de teclado
+                                if dividir(jugador, shoe, reglas) {
+                                    anotar_decision(&mut app, decision, Accion::Dividir, reglas);
+                                    anotar_paso(&mut app, mano, Accion::Dividir, shoe, antes);
+                                    comprobar_turno(&mut app, jugador, banca, reglas);
+                                } else {
+                                    app.mensaje = "No puedes dividir esta mano".to_string();
+                                }
+                            }
+                            KeyCode::Char('5') | KeyCode::Char('r') => {
+                                // Rendirse - atajo de teclado
+                                if rendirse(jugador, reglas, false) {
+                                    anotar_decision(&mut app, decision, Accion::Rendirse, reglas);
+                                    anotar_paso(&mut app, mano, Accion::Rendirse, shoe, antes);
+                                    comprobar_turno(&mut app, jugador, banca, reglas);
+                                } else {
+                                    app.mensaje = "No puedes rendirte ahora".to_string();
+                                }
                             }
                             _ => {}
                         }
                     }
                     GameState::TurnoBanca => {
                         // La banca juega automáticamente
-                        while banca.puntaje() < 17 {
-                            jugar_turno(banca, baraja, true);
-                            banca.puntos = banca.puntaje();
-                        }
+                        jugar_banca(jugador, banca, shoe, reglas);
 
                         // Determinar ganador
-                        let resultado = determinar_ganador(jugador, banca);
-                        app.mensaje = resultado;
+                        let resultado = determinar_ganador(jugador, banca, reglas);
+                        app.mensaje = cerrar_ronda(&mut app, jugador, banca, &resultado);
                         app.estado = GameState::FinJuego;
                         app.actualizar_opciones();
 
                         // Simplemente mostrar el resultado y esperar entrada del usuario
-                        terminal.draw(|frame| render_ui(frame, jugador, banca, &app))?;
-
-                        if key.code == KeyCode::Char('q') {
-                            return Ok(());
+                        terminal.draw(|frame| {
+                            render_ui(frame, jugador, banca, shoe, &app, reglas, indices)
+                        })?;
+                    }
+                    GameState::Examen => match key.code {
+                        KeyCode::Char(c)
+                            if c.is_ascii_digit() || (c == '-' && app.respuesta.is_empty()) =>
+                        {
+                            app.respuesta.push(c);
+                        }
+                        KeyCode::Backspace => {
+                            app.respuesta.pop();
+                        }
+                        KeyCode::Enter => {
+                            if let Ok(respuesta) = app.respuesta.parse::<i32>() {
+                                let cuenta = cuenta_visible(app.sistema, &app, shoe, banca, reglas);
+                                let resultado = if app.puntuacion.responder(respuesta, cuenta) {
+                                    format!("¡Correcto! La cuenta es {:+}.", cuenta)
+                                } else {
+                                    format!("Fallo: la cuenta era {:+}.", cuenta)
+                                };
+                                iniciar_apuesta(&mut app, jugador, reglas);
+                                app.mensaje = format!("{} {}", resultado, app.mensaje);
+                            }
+                        }
+                        KeyCode::Esc => iniciar_apuesta(&mut app, jugador, reglas),
+                        _ => {}
+                    },
+                    GameState::Repeticion => {
+                        if let Some(repeticion) = &mut app.repeticion {
+                            match key.code {
+                                KeyCode::Right | KeyCode::Char(' ') => {
+                                    repeticion.avanzar();
+                                }
+                                KeyCode::Left => {
+                                    repeticion.retroceder();
+                                }
+                                KeyCode::Down | KeyCode::PageDown => {
+                                    repeticion.ir_a_ronda(repeticion.ronda + 1);
+                                }
+                                KeyCode::Up | KeyCode::PageUp => {
+                                    repeticion.ir_a_ronda(repeticion.ronda.saturating_sub(1));
+                                }
+                                KeyCode::Home => {
+                                    repeticion.ir_a_ronda(0);
+                                }
+                                KeyCode::End => {
+                                    repeticion.ir_a_ronda(repeticion.total_rondas() - 1);
+                                }
+                                KeyCode::Char('m') => {
+                                    repeticion.siguiente_error();
+                                }
+                                KeyCode::Char(c) if c.is_ascii_digit() => app.respuesta.push(c),
+                                KeyCode::Backspace => {
+                                    app.respuesta.pop();
+                                }
+                                // Salto a la ronda escrita, contando desde 1
+                                KeyCode::Enter => {
+                                    if let Ok(numero) = app.respuesta.parse::<usize>() {
+                                        repeticion.ir_a_ronda(numero.saturating_sub(1));
+                                    }
+                                    app.respuesta.clear();
+                                }
+                                KeyCode::Esc => {
+                                    volver(&mut app);
+                                }
+                                _ => {}
+                            }
                         }
                     }
+                    GameState::Estadisticas => {
+                        if key.code == KeyCode::Esc {
+                            volver(&mut app);
+                        }
+                    }
+                    GameState::TablaEstrategia => match key.code {
+                        KeyCode::Up => app.cursor_tabla.mover_fila(true),
+                        KeyCode::Down => app.cursor_tabla.mover_fila(false),
+                        KeyCode::Left => app.cursor_tabla.mover_columna(true),
+                        KeyCode::Right => app.cursor_tabla.mover_columna(false),
+                        KeyCode::Esc => {
+                            volver(&mut app);
+                        }
+                        _ => {}
+                    },
+                    GameState::Practica => {
+                        let accion = match key.code {
+                            KeyCode::Enter | KeyCode::Char('1') | KeyCode::Char('p') => {
+                                Some(Accion::Pedir)
+                            }
+                            KeyCode::Char('2') | KeyCode::Char('s') => Some(Accion::Plantarse),
+                            KeyCode::Char('3') | KeyCode::Char('d') => Some(Accion::Doblar),
+                            KeyCode::Char('4') | KeyCode::Char('v') => Some(Accion::Dividir),
+                            KeyCode::Char('5') | KeyCode::Char('r') => Some(Accion::Rendirse),
+                            KeyCode::Char('w') => {
+                                if let Some(practica) = &mut app.practica {
+                                    practica.repaso = !practica.repaso;
+                                    app.mensaje = if practica.repaso {
+                                        "Repaso de puntos débiles activado".to_string()
+                                    } else {
+                                        "Manos al azar, sin repaso".to_string()
+                                    };
+                                }
+                                None
+                            }
+                            KeyCode::Esc => {
+                                volver(&mut app);
+                                None
+                            }
+                            _ => None,
+                        };
+                        if let Some(accion) = accion {
+                            responder_practica(&mut app, accion, reglas);
+                        }
+                    }
+                    GameState::Informe => {
+                        if app.saliendo {
+                            return salir(&app);
+                        }
+                        app.estado = GameState::FinJuego;
+                    }
                     GameState::FinJuego => {
                         match key.code {
                             KeyCode::Enter | KeyCode::Char('n') => {
                                 // Nueva partida - atajo de teclado
-                                reiniciar_partida(jugador, banca, baraja);
-                                app.mensaje = "¡Nueva partida!".to_string();
-                                app.estado = GameState::TurnoJugador;
-                                app.actualizar_opciones();
+                                let barajado = reiniciar_partida(jugador, banca, shoe);
+                                app.rondas += 1;
+                                iniciar_apuesta(&mut app, jugador, reglas);
+                                if barajado {
+                                    app.mensaje = format!("Se baraja el shoe. {}", app.mensaje);
+                                } else if app.conteo == ModoConteo::Examen
+                                    && app.rondas.is_multiple_of(RONDAS_EXAMEN)
+                                {
+                                    app.estado = GameState::Examen;
+                                    app.respuesta.clear();
+                                    app.mensaje = format!(
+                                        "¿Cuál es la cuenta corriente ({})?",
+                                        app.sistema.nombre()
+                                    );
+                                }
                             }
-                            KeyCode::Char('q') => {
-                                return Ok(());
+                            KeyCode::Char('e') => {
+                                app.saliendo = false;
+                                app.estado = GameState::Informe;
                             }
                             _ => {}
                         }
@@ -151,18 +750,447 @@ pub fn run_game<B: Backend>(
     }
 }
 
-// Función para reiniciar la partida
-fn reiniciar_partida(jugador: &mut Jugador, banca: &mut Jugador, baraja: &mut Vec<Carta>) {
-    jugador.mano.clear();
-    banca.mano.clear();
-    jugador.puntos = 0;
-    banca.puntos = 0;
+// Empieza a jugar con el perfil elegido, o como invitado si no hay ninguno
+fn elegir_perfil(
+    app: &mut AppState,
+    jugador: &mut Jugador,
+    reglas: &ReglasMesa,
+    perfil: Option<usize>,
+) {
+    app.perfil = perfil;
+    if let Some(perfil) = perfil {
+        let nombre = app.perfiles.perfiles[perfil].nombre.clone();
+        app.perfiles.ultimo = Some(nombre.clone());
+        jugador.nombre = nombre;
+    }
+    iniciar_apuesta(app, jugador, reglas);
+}
+
+// Al salir se guardan las estadísticas del perfil con el que se ha jugado
+fn salir(app: &AppState) -> io::Result<()> {
+    if app.perfil.is_some() {
+        config::guardar_perfiles(&app.perfiles).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+// Pasa a la fase de apuestas ajustando la apuesta al saldo disponible
+fn iniciar_apuesta(app: &mut AppState, jugador: &Jugador, reglas: &ReglasMesa) {
+    app.estado = GameState::Apuesta;
+    app.actualizar_opciones();
+
+    if jugador.saldo < reglas.apuesta_minima {
+        app.mensaje = "No te quedan fichas suficientes para seguir jugando".to_string();
+        return;
+    }
+    app.apuesta = app.apuesta.clamp(
+        reglas.apuesta_minima,
+        reglas.apuesta_maxima.min(jugador.saldo),
+    );
+    app.mensaje = format!("Haz tu apuesta: {}", app.apuesta);
+}
+
+// Hay que decidir antes del peek si se ofrece seguro o rendición temprana
+fn hay_decision_previa(jugador: &Jugador, banca: &Jugador, reglas: &ReglasMesa) -> bool {
+    let rendicion_temprana = !reglas.peek.sin_carta_oculta()
+        && banca_puede_tener_blackjack(banca)
+        && puede_rendirse(jugador, reglas, true);
+    ofrecer_seguro(banca, reglas) || rendicion_temprana
+}
+
+// Revisa los blackjacks tras el reparto y las decisiones previas (seguro, dinero par, rendición)
+fn revisar_reparto(
+    app: &mut AppState,
+    jugador: &mut Jugador,
+    banca: &mut Jugador,
+    reglas: &ReglasMesa,
+    aviso: Option<String>,
+) {
+    let mut mensajes: Vec<String> = aviso.into_iter().collect();
+    app.estado = GameState::TurnoJugador;
+    app.actualizar_opciones();
+
+    // Con carta oculta, el seguro se resuelve al mirar la banca
+    if !reglas.peek.sin_carta_oculta() {
+        mensajes.extend(resolver_seguro(jugador, banca).map(mensaje_seguro));
+    }
+
+    // Los blackjacks naturales se liquidan al momento
+    if let Some(resultado) = revisar_blackjack(jugador, banca, reglas) {
+        mensajes.push(cerrar_ronda(app, jugador, banca, &resultado));
+        app.estado = GameState::FinJuego;
+        app.actualizar_opciones();
+    } else {
+        comprobar_turno(app, jugador, banca, reglas);
+    }
+
+    if !mensajes.is_empty() {
+        app.mensaje = mensajes.join(" ");
+    }
+}
+
+// Tras cada acción del jugador, decide si sigue su turno, juega la banca o acaba la partida
+fn comprobar_turno(
+    app: &mut AppState,
+    jugador: &mut Jugador,
+    banca: &mut Jugador,
+    reglas: &ReglasMesa,
+) {
+    if !jugador.turno_terminado() {
+        if jugador.manos.len() > 1 {
+            app.mensaje = format!(
+                "Mano {} de {}",
+                jugador.mano_activa + 1,
+                jugador.manos.len()
+            );
+        }
+        return;
+    }
+
+    // Con carta oculta ya revisada, la banca no juega si ninguna mano la necesita
+    let sin_banca = !jugador.necesita_banca() && !reglas.peek.sin_carta_oculta();
+    if jugador.todas_pasadas() || sin_banca {
+        let resultado = determinar_ganador(jugador, banca, reglas);
+        app.mensaje = cerrar_ronda(app, jugador, banca, &resultado);
+        app.estado = GameState::FinJuego;
+        app.actualizar_opciones();
+    } else {
+        app.estado = GameState::TurnoBanca;
+        app.mensaje = "Turno de la banca".to_string();
+    }
+}
+
+// Carga el historial de manos y pasa a repasarlo desde la última ronda
+fn abrir_repeticion(app: &mut AppState) {
+    let (rondas, descartadas) = match historial::cargar() {
+        Ok(cargado) => cargado,
+        Err(error) => {
+            app.mensaje = error;
+            return;
+        }
+    };
+    let aviso = historial::aviso_descartadas(descartadas);
+    match Repeticion::nueva(rondas) {
+        Some(repeticion) => {
+            app.repeticion = Some(repeticion);
+            app.respuesta.clear();
+            let previo = std::mem::replace(&mut app.estado, GameState::Repeticion);
+            app.estado_previo.push((previo, app.mensaje.clone()));
+            // El mensaje anterior vuelve al cerrar la repetición
+            app.mensaje = aviso.unwrap_or_default();
+        }
+        None => {
+            app.mensaje = aviso.unwrap_or_else(|| "Aún no hay rondas en el historial".to_string())
+        }
+    }
+}
+
+// Agrupa los resultados del historial y abre el panel de estadísticas
+fn abrir_estadisticas(app: &mut AppState) {
+    let (rondas, descartadas) = match historial::cargar() {
+        Ok(cargado) => cargado,
+        Err(error) => {
+            app.mensaje = error;
+            return;
+        }
+    };
+    let aviso = historial::aviso_descartadas(descartadas);
+    if rondas.is_empty() {
+        app.mensaje = aviso.unwrap_or_else(|| "Aún no hay rondas en el historial".to_string());
+        return;
+    }
+    app.tendencias = Some(Tendencias::nuevas(&rondas));
+    let previo = std::mem::replace(&mut app.estado, GameState::Estadisticas);
+    app.estado_previo.push((previo, app.mensaje.clone()));
+    app.mensaje = format!("Estadísticas de {} rondas del historial", rondas.len());
+    if let Some(aviso) = aviso {
+        app.mensaje = format!("{} ({})", app.mensaje, aviso.to_lowercase());
+    }
+}
+
+// Empieza el modo práctica con los puntos débiles de sesiones anteriores
+fn abrir_practica(app: &mut AppState) {
+    let puntos = match config::cargar_practica() {
+        Ok(puntos) => puntos,
+        Err(error) => {
+            app.mensaje = error;
+            return;
+        }
+    };
+    let practica = Entrenamiento::nuevo(puntos);
+    let previo = std::mem::replace(&mut app.estado, GameState::Practica);
+    app.estado_previo.push((previo, app.mensaje.clone()));
+    app.mensaje = if practica.repaso {
+        "Práctica con repaso de puntos débiles: ¿qué haces?".to_string()
+    } else {
+        "Práctica: ¿qué haces?".to_string()
+    };
+    app.practica = Some(practica);
+}
+
+// Corrige la respuesta al momento y guarda los aciertos de la situación
+fn responder_practica(app: &mut AppState, accion: Accion, reglas: &ReglasMesa) {
+    let Some(practica) = &mut app.practica else {
+        return;
+    };
+    let Some(respuesta) = practica.responder(accion, reglas) else {
+        app.mensaje = format!("No puedes {} con esta mano", accion.nombre().to_lowercase());
+        return;
+    };
+    let segundos = respuesta.milisegundos as f64 / 1000.0;
+    app.mensaje = if respuesta.acierto() {
+        format!(
+            "✓ {}: {} ({:.1} s)",
+            respuesta.situacion.descripcion(),
+            accion.nombre(),
+            segundos
+        )
+    } else {
+        format!(
+            "✗ {}: la estrategia básica dice {}, no {} ({:.1} s)",
+            respuesta.situacion.descripcion(),
+            respuesta.correcta.accion.nombre(),
+            accion.nombre(),
+            segundos
+        )
+    };
+    if let Err(error) = config::guardar_practica(&practica.puntos) {
+        app.mensaje = format!("{} (Práctica no guardada: {})", app.mensaje, error);
+    }
+}
+
+// Cierra la repetición, las estadísticas, la tabla o la práctica y vuelve al estado anterior
+fn volver(app: &mut AppState) -> bool {
+    let Some((estado, mensaje)) = app.estado_previo.pop() else {
+        return false;
+    };
+    match app.estado {
+        GameState::Repeticion => app.repeticion = None,
+        GameState::Estadisticas => app.tendencias = None,
+        GameState::Practica => app.practica = None,
+        _ => {}
+    }
+    app.estado = estado;
+    app.mensaje = mensaje;
+    true
+}
+
+// Anota una jugada en la ronda en curso con las cartas que ha sacado del shoe
+fn anotar_paso(app: &mut AppState, mano: usize, accion: Accion, shoe: &Shoe, antes: usize) {
+    if let Some(ronda) = &mut app.ronda {
+        // Si el shoe se ha rebarajado durante la jugada, todas sus cartas son nuevas
+        let cartas = shoe.repartidas().get(antes..).unwrap_or(shoe.repartidas());
+        ronda.anotar(mano, accion, cartas);
+    }
+}
+
+// Guarda la ronda liquidada en el historial y devuelve el mensaje con su resultado
+fn cerrar_ronda(
+    app: &mut AppState,
+    jugador: &Jugador,
+    banca: &Jugador,
+    liquidacion: &Liquidacion,
+) -> String {
+    let mut mensaje = liquidacion.mensaje();
+    if let Some(mut ronda) = app.ronda.take() {
+        ronda.terminar(jugador, banca, liquidacion);
+        if let Some(perfil) = app.perfil {
+            app.perfiles.perfiles[perfil].estadisticas.anotar(&ronda);
+        }
+        if let Err(error) = historial::anotar(&ronda) {
+            mensaje = format!("{} (Historial no guardado: {})", mensaje, error);
+        }
+    }
+    mensaje
+}
+
+// Cuenta de las cartas vistas: la carta oculta de la banca no cuenta hasta que se descubre
+fn cuenta_visible(
+    sistema: SistemaConteo,
+    app: &AppState,
+    shoe: &Shoe,
+    banca: &Jugador,
+    reglas: &ReglasMesa,
+) -> i32 {
+    let mut cuenta = shoe.cuenta(sistema, reglas.mazos);
+    if !app.mostrar_todas_cartas_banca {
+        if let Some(oculta) = banca.mano().cartas.get(1) {
+            cuenta -= sistema.valor(oculta);
+        }
+    }
+    cuenta
+}
+
+// Cartas que el jugador aún no ha visto. Con CSM los descartes vuelven al shoe,
+// así que solo faltan las cartas que hay en la mesa
+fn composicion_visible(
+    app: &AppState,
+    jugador: &Jugador,
+    banca: &Jugador,
+    shoe: &Shoe,
+    reglas: &ReglasMesa,
+) -> Composicion {
+    let mut composicion = Composicion::completa(reglas.mazos);
+    match shoe.tipo() {
+        TipoShoe::Continuo => {
+            let mesa = jugador.manos.iter().flat_map(|mano| &mano.cartas);
+            for carta in mesa.chain(banca.mano().cartas.first()) {
+                composicion.quitar(carta);
+            }
+        }
+        TipoShoe::CartaCorte => {
+            for carta in shoe.repartidas() {
+                composicion.quitar(carta);
+            }
+            if !app.mostrar_todas_cartas_banca {
+                if let Some(oculta) = banca.mano().cartas.get(1) {
+                    composicion.devolver(oculta);
+                }
+            }
+        }
+    }
+    composicion
+}
+
+// Calcula el EV exacto de la decisión actual, solo si ha cambiado desde el último cálculo
+fn actualizar_ev(
+    app: &mut AppState,
+    jugador: &Jugador,
+    banca: &Jugador,
+    shoe: &Shoe,
+    reglas: &ReglasMesa,
+) {
+    let decision = match app.estado {
+        GameState::TurnoJugador => decision_actual(jugador, banca, reglas, false),
+        _ => None,
+    };
+    let Some((situacion, opciones)) = decision else {
+        app.ev = None;
+        return;
+    };
+    let clave = (
+        situacion,
+        app.rondas,
+        shoe.repartidas().len(),
+        jugador.mano_activa,
+    );
+    if app
+        .ev
+        .as_ref()
+        .is_some_and(|(anterior, _)| *anterior == clave)
+    {
+        return;
+    }
+
+    let composicion = composicion_visible(app, jugador, banca, shoe, reglas);
+    let jugadas =
+        EvaluadorExacto::nuevo(composicion, situacion.banca, reglas).jugadas(&situacion, opciones);
+    app.ev = Some((clave, jugadas));
+}
+
+// Carta de la banca de la que se calculan los finales, mientras la otra siga oculta
+fn carta_visible_banca<'a>(app: &AppState, banca: &'a Jugador) -> Option<&'a Carta> {
+    banca
+        .mano()
+        .cartas
+        .first()
+        .filter(|_| !app.mostrar_todas_cartas_banca)
+}
+
+// Calcula los finales de la banca, solo si ha cambiado lo que se sabe de ella
+fn actualizar_finales_banca(
+    app: &mut AppState,
+    jugador: &Jugador,
+    banca: &Jugador,
+    shoe: &Shoe,
+    reglas: &ReglasMesa,
+) {
+    let Some(carta) = carta_visible_banca(app, banca) else {
+        app.finales_banca = None;
+        return;
+    };
+    let clave = (
+        carta.puntos(),
+        app.rondas,
+        shoe.repartidas().len(),
+        app.mostrar_todas_cartas_banca,
+    );
+    if app
+        .finales_banca
+        .is_some_and(|(anterior, _)| anterior == clave)
+    {
+        return;
+    }
+
+    let composicion = composicion_visible(app, jugador, banca, shoe, reglas);
+    let finales = EvaluadorExacto::nuevo(composicion, carta.puntos(), reglas).resultados_banca();
+    app.finales_banca = Some((clave, finales));
+}
 
-    *baraja = crate::game::deck::crear_baraja();
-    repartir_cartas(jugador, banca, baraja);
+// Anota la decisión tomada frente a la estrategia básica
+fn anotar_decision(
+    app: &mut AppState,
+    decision: Option<(Situacion, Opciones)>,
+    accion: Accion,
+    reglas: &ReglasMesa,
+) {
+    if let Some((situacion, opciones)) = decision {
+        app.registro.registrar(situacion, opciones, accion, reglas);
+    }
 }
 
-fn render_ui(frame: &mut ratatui::Frame, jugador: &Jugador, banca: &Jugador, app: &AppState) {
+fn render_ui(
+    frame: &mut ratatui::Frame,
+    jugador: &Jugador,
+    banca: &Jugador,
+    shoe: &Shoe,
+    app: &AppState,
+    reglas: &ReglasMesa,
+    indices: &TablaIndices,
+) {
+    // Create a string representation of cards
+    fn texto_cartas(cartas: &[Carta], visibles: usize) -> String {
+        let mut mano = String::new();
+
+        if cartas.is_empty() {
+            return "[Sin cartas]".to_string();
+        }
+
+        // Add visible cards
+        for carta in cartas.iter().take(visibles) {
+            let carta_str = format!(
+                "\n╭─────╮\n│{:^5}│\n│{:^5}│\n│{:^5}│\n╰─────╯\n",
+                carta.valor_str(),
+                carta.simbolo(),
+                carta.valor_str()
+            );
+            mano.push_str(&carta_str);
+            mano.push(' ');
+        }
+
+        // Add hidden cards
+        if cartas.len() > visibles {
+            mano.push_str("\n+ ocultas");
+        }
+        mano
+    }
+
+    fn texto_puntos(mano: &Mano) -> String {
+        // Las manos blandas muestran los dos valores posibles
+        let valor = mano.valor();
+        let puntos = if valor.blanda && valor.total < 21 {
+            format!("{}/{}", valor.total - 10, valor.total)
+        } else {
+            valor.total.to_string()
+        };
+
+        if mano.doblada {
+            format!("{} (doblada)", puntos)
+        } else {
+            puntos
+        }
+    }
+
     // Function to render a player
     fn render_player(
         frame: &mut ratatui::Frame,
@@ -172,40 +1200,8 @@ fn render_ui(frame: &mut ratatui::Frame, jugador: &Jugador, banca: &Jugador, app
         mostrar_todas_cartas: bool,
         color: Color,
     ) {
-        // Create a string representation of cards
-        let mut mano = String::new();
-
-        if jugador.mano.is_empty() {
-            mano = "[Sin cartas]".to_string();
-        } else {
-            // Define how many cards to show
-            let cards_to_show = if nombre == "Banca" && !mostrar_todas_cartas {
-                1 // Only show first card for the bank when hidden
-            } else {
-                jugador.mano.len() // Show all cards otherwise
-            };
-
-            // Add visible cards
-            for i in 0..cards_to_show {
-                let carta = &jugador.mano[i];
-                let carta_str = format!(
-                    "\n╭─────╮\n│{:^5}│\n│{:^5}│\n│{:^5}│\n╰─────╯\n",
-                    carta.valor_str(),
-                    carta.simbolo(),
-                    carta.valor_str()
-                );
-                mano.push_str(&carta_str);
-                mano.push(' ');
-            }
-
-            // Add hidden cards for the bank
-            if nombre == "Banca" && !mostrar_todas_cartas && jugador.mano.len() > 1 {
-                mano.push_str(&format!("\n+ ocultas"));
-            }
-        }
-
         let puntos = if mostrar_todas_cartas {
-            jugador.puntos.to_string()
+            texto_puntos(jugador.mano())
         } else {
             "?".to_string()
         };
@@ -224,35 +1220,140 @@ fn render_ui(frame: &mut ratatui::Frame, jugador: &Jugador, banca: &Jugador, app
             )
             .title_bottom(
                 Span::styled(
-                    format!("Ganadas: {}", jugador.partidas_ganadas),
+                    if nombre == "Banca" {
+                        format!("Ganadas: {}", jugador.partidas_ganadas)
+                    } else {
+                        let seguro = if jugador.seguro > 0 {
+                            format!(" | Seguro: {}", jugador.seguro)
+                        } else {
+                            String::new()
+                        };
+                        format!(
+                            "Saldo: {} | Apuesta: {}{} | Ganadas: {}",
+                            jugador.saldo,
+                            jugador.apuesta_total(),
+                            seguro,
+                            jugador.partidas_ganadas
+                        )
+                    },
                     Style::default().fg(Color::White),
                 )
                 .into_centered_line(),
             );
 
-        let widget = Paragraph::new(mano)
-            .style(Style::default().fg(Color::White))
-            .block(block)
-            .centered();
-        frame.render_widget(widget, area);
+        // Define how many cards to show
+        if jugador.manos.len() == 1 {
+            let mano = jugador.mano();
+            let visibles = if mostrar_todas_cartas {
+                mano.cartas.len()
+            } else {
+                1
+            };
+            let widget = Paragraph::new(texto_cartas(&mano.cartas, visibles))
+                .style(Style::default().fg(Color::White))
+                .block(block)
+                .centered();
+            frame.render_widget(widget, area);
+            return;
+        }
+
+        // Each split hand gets its own column, highlighting the active one
+        let interior = block.inner(area);
+        frame.render_widget(block, area);
+
+        let columnas = Layout::horizontal(
+            jugador
+                .manos
+                .iter()
+                .map(|_| Constraint::Ratio(1, jugador.manos.len() as u32)),
+        )
+        .split(interior);
+
+        for (i, mano) in jugador.manos.iter().enumerate() {
+            let estilo = if i == jugador.mano_activa && !mano.terminada {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let sub_block = Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(estilo)
+                .title_top(Span::styled(
+                    format!("Mano {}", i + 1),
+                    Style::default().fg(Color::White),
+                ))
+                .title_top(
+                    Span::styled(texto_puntos(mano), Style::default().fg(Color::White))
+                        .into_right_aligned_line(),
+                );
+            let widget = Paragraph::new(texto_cartas(&mano.cartas, mano.cartas.len()))
+                .style(Style::default().fg(Color::White))
+                .block(sub_block)
+                .centered();
+            frame.render_widget(widget, columnas[i]);
+        }
     }
 
+    // Ronda del historial que se está repasando, si la hay
+    let repeticion = match app.estado {
+        GameState::Repeticion => app.repeticion.as_ref(),
+        _ => None,
+    };
+
     // Main vertical layout
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Título
             Constraint::Length(1), // Mensaje
+            Constraint::Length(1), // Consejo
+            // EV de cada jugada o barra de tiempo de la repetición
+            Constraint::Length(if app.mostrar_ev || repeticion.is_some() {
+                1
+            } else {
+                0
+            }),
             Constraint::Min(3),    // Mesa (Banca y Jugador horizontalmente)
             Constraint::Length(1), // Footer
         ])
         .split(frame.size());
 
-    // Horizontal layout for Banca y Jugador
-    let mesa_chunks = Layout::horizontal([Constraint::Min(10), Constraint::Min(10)])
-        .vertical_margin(2)
-        .spacing(3)
-        .split(main_chunks[2]);
+    // Horizontal layout for Banca y Jugador, con el panel de probabilidades de la banca a la derecha
+    let mesa_chunks = Layout::horizontal([
+        Constraint::Min(10),
+        Constraint::Min(10),
+        Constraint::Length(if app.mostrar_banca { 30 } else { 0 }),
+    ])
+    .vertical_margin(2)
+    .spacing(3)
+    .split(main_chunks[4]);
+
+    // Conteo de cartas según el modo elegido
+    let cuenta = cuenta_visible(app.sistema, app, shoe, banca, reglas);
+    let sistema = app.sistema.nombre();
+    let texto_conteo = match app.conteo {
+        ModoConteo::Desactivado => String::new(),
+        ModoConteo::Corriente => format!("{}: RC {:+}", sistema, cuenta),
+        ModoConteo::Real if !app.sistema.balanceado() => {
+            format!("{}: RC {:+} (sin cuenta real)", sistema, cuenta)
+        }
+        ModoConteo::Real => format!(
+            "{}: RC {:+} | TC {:+.1}",
+            sistema,
+            cuenta,
+            cuenta_real(cuenta, shoe.restantes())
+        ),
+        ModoConteo::Examen => format!(
+            "{}: cuenta oculta | Aciertos {}/{} ({:.0}%)",
+            sistema,
+            app.puntuacion.aciertos,
+            app.puntuacion.preguntas,
+            app.puntuacion.precision() * 100.0
+        ),
+    };
 
     // Título
     let titulo = Paragraph::new("♤ ♡ RATJACK ♢ ♧")
@@ -265,43 +1366,1052 @@ fn render_ui(frame: &mut ratatui::Frame, jugador: &Jugador, banca: &Jugador, app
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_type(BorderType::Rounded),
+                .border_type(BorderType::Rounded)
+                .title_bottom(
+                    Span::styled(
+                        match repeticion.map(|repeticion| repeticion.ronda_actual()) {
+                            Some(ronda) => format!(
+                                "{} | Semilla: {} | Carta {}",
+                                ronda.reglas.nombre,
+                                ronda.semilla,
+                                ronda.posicion + 1
+                            ),
+                            None => format!("{} | Semilla: {}", reglas.nombre, shoe.semilla()),
+                        },
+                        Style::default().fg(Color::DarkGray),
+                    )
+                    .into_left_aligned_line(),
+                )
+                .title_bottom(
+                    Span::styled(texto_conteo, Style::default().fg(Color::Magenta))
+                        .into_centered_line(),
+                )
+                .title_bottom(
+                    Span::styled(
+                        format!(
+                            "{}: {}/{} | Descartes: {}",
+                            match shoe.tipo() {
+                                TipoShoe::CartaCorte => "Shoe",
+                                TipoShoe::Continuo => "CSM",
+                            },
+                            shoe.restantes(),
+                            shoe.total(),
+                            shoe.en_bandeja()
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    )
+                    .into_right_aligned_line(),
+                ),
         );
     frame.render_widget(titulo, main_chunks[0]);
 
     // Mensaje
-    let mensaje = Paragraph::new(app.mensaje.clone())
+    let texto_mensaje = match app.estado {
+        GameState::Examen | GameState::NuevoPerfil => format!("{} {}_", app.mensaje, app.respuesta),
+        GameState::Repeticion if !app.respuesta.is_empty() => {
+            format!("Ir a la ronda: {}_", app.respuesta)
+        }
+        _ => match repeticion {
+            // Durante la repetición el mensaje solo lleva avisos al cargar el historial
+            Some(repeticion) if !app.mensaje.is_empty() => format!(
+                "Paso {}/{}: {} ({})",
+                repeticion.paso + 1,
+                repeticion.total_pasos(),
+                repeticion.fotograma().descripcion,
+                app.mensaje.to_lowercase()
+            ),
+            Some(repeticion) => format!(
+                "Paso {}/{}: {}",
+                repeticion.paso + 1,
+                repeticion.total_pasos(),
+                repeticion.fotograma().descripcion
+            ),
+            None => app.mensaje.clone(),
+        },
+    };
+    let mensaje = Paragraph::new(texto_mensaje)
         .style(Style::default().fg(Color::Yellow))
         .alignment(Alignment::Center);
     frame.render_widget(mensaje, main_chunks[1]);
 
-    render_player(
-        frame,
-        mesa_chunks[0],
-        "Banca",
-        &banca,
-        app.mostrar_todas_cartas_banca,
-        Color::Red,
-    );
-    render_player(
-        frame,
-        mesa_chunks[1],
-        &jugador.nombre,
-        &jugador,
-        true,
-        Color::Blue,
-    );
+    // En la repetición, cada jugada se compara con la estrategia básica
+    if let Some(repeticion) = repeticion {
+        let (texto, color) = match &repeticion.fotograma().decision {
+            Some(decision) if decision.es_error() => (
+                format!(
+                    "✗ Error: la estrategia básica dice {} ({:.3} apuestas de EV perdidas)",
+                    decision.correcta.nombre(),
+                    decision.coste
+                ),
+                Color::Red,
+            ),
+            Some(_) => ("✓ Jugada de estrategia básica".to_string(), Color::Green),
+            None => (String::new(), Color::Cyan),
+        };
+        let anotacion = Paragraph::new(texto)
+            .style(Style::default().fg(color))
+            .alignment(Alignment::Center);
+        frame.render_widget(anotacion, main_chunks[2]);
+    } else if app.mostrar_consejo {
+        // Consejo de estrategia básica para la decisión actual
+        let consejo = match app.estado {
+            GameState::DecisionPrevia => match recomendar(jugador, banca, reglas, true) {
+                Some(Accion::Rendirse) => Some(Accion::Rendirse.nombre()),
+                _ if ofrecer_seguro(banca, reglas) => Some("No tomar seguro"),
+                _ => Some("Seguir"),
+            },
+            GameState::TurnoJugador => {
+                recomendar(jugador, banca, reglas, false).map(|accion| accion.nombre())
+            }
+            _ => None,
+        };
+        let mut texto = match consejo {
+            Some(consejo) => format!("Estrategia básica: {}", consejo),
+            None => "Estrategia básica: sin decisión pendiente".to_string(),
+        };
+
+        // Con la cuenta a la vista se añaden las jugadas de índice que cambian la recomendación
+        if matches!(app.conteo, ModoConteo::Corriente | ModoConteo::Real) {
+            let corriente = cuenta_visible(indices.sistema, app, shoe, banca, reglas);
+            let cuenta = cuenta_indices(indices.sistema, corriente, shoe.restantes());
+            let desviacion = match app.estado {
+                GameState::DecisionPrevia
+                    if ofrecer_seguro(banca, reglas) && indices.tomar_seguro(cuenta) =>
+                {
+                    Some(format!(
+                        "Tomar seguro (cuenta ≥ {:+})",
+                        indices.seguro.unwrap_or_default()
+                    ))
+                }
+                GameState::TurnoJugador => decision_actual(jugador, banca, reglas, false).and_then(
+                    |(situacion, opciones)| {
+                        let (accion, indice) =
+                            indices.recomendar(situacion, reglas, opciones, cuenta);
+                        indice
+                            .map(|indice| format!("{} ({})", accion.nombre(), indice.descripcion()))
+                    },
+                ),
+                _ => None,
+            };
+            if let Some(desviacion) = desviacion {
+                texto = format!("{} | Con la cuenta {:+.1}: {}", texto, cuenta, desviacion);
+            }
+        }
+        let consejo = Paragraph::new(texto)
+            .style(Style::default().fg(Color::Cyan))
+            .alignment(Alignment::Center);
+        frame.render_widget(consejo, main_chunks[2]);
+    }
+
+    // Barra de tiempo con la posición en todo el historial
+    if let Some(repeticion) = repeticion {
+        let errores = match repeticion.errores() {
+            0 => String::new(),
+            1 => " · 1 error".to_string(),
+            errores => format!(" · {} errores", errores),
+        };
+        let linea_tiempo = LineGauge::default()
+            .label(format!(
+                "Ronda {}/{}{} ",
+                repeticion.ronda + 1,
+                repeticion.total_rondas(),
+                errores
+            ))
+            .ratio(repeticion.progreso())
+            .line_set(symbols::line::THICK)
+            .gauge_style(Style::default().fg(Color::Yellow).bg(Color::DarkGray));
+        frame.render_widget(linea_tiempo, main_chunks[3]);
+    } else if app.mostrar_ev {
+        // Valor esperado exacto de cada jugada con las cartas que quedan, la mejor resaltada.
+        // El de dividir es aproximado y se marca con ≈
+        let mut linea = vec![Span::raw("EV exacto: ")];
+        match &app.ev {
+            Some((_, jugadas)) => {
+                let mejor = jugadas.iter().map(|(_, ev)| *ev).fold(f64::MIN, f64::max);
+                for (i, (accion, ev)) in jugadas.iter().enumerate() {
+                    if i > 0 {
+                        linea.push(Span::raw(" | "));
+                    }
+                    let estilo = if *ev == mejor {
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    let aproximado = if *accion == Accion::Dividir {
+                        "≈"
+                    } else {
+                        ""
+                    };
+                    linea.push(Span::styled(
+                        format!("{} {}{:+.3}", accion.nombre(), aproximado, ev),
+                        estilo,
+                    ));
+                }
+            }
+            None => linea.push(Span::raw("sin decisión pendiente")),
+        }
+        let ev = Paragraph::new(Line::from(linea))
+            .style(Style::default().fg(Color::Cyan))
+            .alignment(Alignment::Center);
+        frame.render_widget(ev, main_chunks[3]);
+    }
+
+    if let GameState::Informe = app.estado {
+        render_informe(frame, main_chunks[4], &app.registro);
+    } else if let (GameState::Estadisticas, Some(tendencias)) = (&app.estado, &app.tendencias) {
+        render_estadisticas(frame, main_chunks[4], tendencias);
+    } else if let GameState::TablaEstrategia = app.estado {
+        render_tabla_estrategia(frame, main_chunks[4], app.cursor_tabla, reglas);
+    } else if let (GameState::Practica, Some(practica)) = (&app.estado, &app.practica) {
+        let chunks = Layout::horizontal([
+            Constraint::Min(10),
+            Constraint::Min(10),
+            Constraint::Length(52),
+        ])
+        .vertical_margin(2)
+        .spacing(3)
+        .split(main_chunks[4]);
+        // Manos sueltas solo para dibujar las cartas de la pregunta
+        let mano = |cartas: &[Carta]| {
+            let mut mano = Jugador::nuevo();
+            mano.manos[0].cartas = cartas.to_vec();
+            mano
+        };
+        let pregunta = &practica.pregunta;
+        render_player(
+            frame,
+            chunks[0],
+            "Banca",
+            &mano(&[pregunta.banca]),
+            true,
+            Color::Red,
+        );
+        render_player(
+            frame,
+            chunks[1],
+            &jugador.nombre,
+            &mano(&pregunta.jugador),
+            true,
+            Color::Blue,
+        );
+        render_practica(frame, chunks[2], practica);
+    } else if let GameState::Inicio | GameState::NuevoPerfil = app.estado {
+        render_perfiles(frame, main_chunks[4], app);
+    } else if let Some(repeticion) = repeticion {
+        let fotograma = repeticion.fotograma();
+        render_player(
+            frame,
+            mesa_chunks[0],
+            "Banca",
+            &fotograma.banca,
+            fotograma.banca_descubierta,
+            Color::Red,
+        );
+        render_player(
+            frame,
+            mesa_chunks[1],
+            &jugador.nombre,
+            &fotograma.jugador,
+            true,
+            Color::Blue,
+        );
+    } else {
+        render_player(
+            frame,
+            mesa_chunks[0],
+            "Banca",
+            banca,
+            app.mostrar_todas_cartas_banca,
+            Color::Red,
+        );
+        render_player(
+            frame,
+            mesa_chunks[1],
+            &jugador.nombre,
+            jugador,
+            true,
+            Color::Blue,
+        );
+        if app.mostrar_banca {
+            render_probabilidades_banca(frame, mesa_chunks[2], app, banca, reglas);
+        }
+    }
 
     // Footer con todos los comandos disponibles
     let footer_text = match app.estado {
-        GameState::Inicio => "↵:Comenzar | q:Salir",
-        GameState::TurnoJugador => "↵/1/p:Pedir | 2/s:Plantarse | q:Salir",
-        GameState::FinJuego => "↵/n:Nueva Partida | q:Salir",
-        _ => "q:Salir",
+        GameState::Inicio => {
+            "↑/↓:Perfil | ↵:Comenzar | r:Repetición | g:Estadísticas | t:Tabla | a:Práctica | c:Conteo | k:Sistema | q:Salir".to_string()
+        }
+        GameState::NuevoPerfil => "↵:Crear perfil | Esc:Volver".to_string(),
+        GameState::Apuesta => {
+            "+/-:Cambiar apuesta | ↵:Repartir | r:Repetición | g:Estadísticas | t:Tabla | a:Práctica | c:Conteo | k:Sistema | q:Salir"
+                .to_string()
+        }
+        GameState::DecisionPrevia => {
+            let mut opciones = Vec::new();
+            if ofrecer_seguro(banca, reglas) && jugador.mano().es_blackjack() {
+                opciones.push("s/1:Dinero par");
+            } else if ofrecer_seguro(banca, reglas) {
+                opciones.push("s/1:Tomar seguro");
+            }
+            opciones.push("n/2:Seguir");
+            if puede_rendirse(jugador, reglas, true) {
+                opciones.push("r/5:Rendirse");
+            }
+            opciones.push("h:Consejo");
+            opciones.push("b:Banca");
+            opciones.push("q:Salir");
+            opciones.join(" | ")
+        }
+        GameState::TurnoJugador if puede_rendirse(jugador, reglas, false) => {
+            "↵/1/p:Pedir | 2/s:Plantarse | 3/d:Doblar | 4/v:Dividir | 5/r:Rendirse | h:Consejo | x:EV | b:Banca | q:Salir"
+                .to_string()
+        }
+        GameState::TurnoJugador => {
+            "↵/1/p:Pedir | 2/s:Plantarse | 3/d:Doblar | 4/v:Dividir | h:Consejo | x:EV | b:Banca | q:Salir".to_string()
+        }
+        GameState::FinJuego => {
+            "↵/n:Nueva Partida | e:Errores | r:Repetición | g:Estadísticas | t:Tabla | a:Práctica | c:Conteo | k:Sistema | q:Salir"
+                .to_string()
+        }
+        GameState::Examen => "0-9/-:Cuenta | ↵:Responder | Esc:Saltar | q:Salir".to_string(),
+        GameState::Repeticion => {
+            "←/→:Paso | ↑/↓:Ronda | 0-9 ↵:Ir a ronda | m:Siguiente error | Esc:Volver | q:Salir"
+                .to_string()
+        }
+        GameState::Estadisticas => "g/Esc:Volver | q:Salir".to_string(),
+        GameState::TablaEstrategia => "←/→/↑/↓:Casilla | t/Esc:Volver | q:Salir".to_string(),
+        GameState::Practica => {
+            "↵/1/p:Pedir | 2/s:Plantarse | 3/d:Doblar | 4/v:Dividir | 5/r:Rendirse | w:Repaso | a/Esc:Volver | q:Salir".to_string()
+        }
+        GameState::Informe if app.saliendo => "Pulsa cualquier tecla para salir".to_string(),
+        GameState::Informe => "Pulsa cualquier tecla para volver".to_string(),
+        _ => "q:Salir".to_string(),
     };
 
     let footer = Paragraph::new(footer_text)
         .style(Style::default().fg(Color::DarkGray))
         .alignment(Alignment::Center);
-    frame.render_widget(footer, main_chunks[3]);
+    frame.render_widget(footer, main_chunks[5]);
+}
+
+// Probabilidad de cada total final de la banca según su carta visible y las cartas que quedan
+fn render_probabilidades_banca(
+    frame: &mut ratatui::Frame,
+    area: ratatui::layout::Rect,
+    app: &AppState,
+    banca: &Jugador,
+    reglas: &ReglasMesa,
+) {
+    let regla = match reglas.banca {
+        ReglaBanca::S17 => "S17",
+        ReglaBanca::H17 => "H17",
+    };
+    let carta_visible = carta_visible_banca(app, banca);
+    let titulo = match carta_visible {
+        Some(carta) => format!("Banca con {} ({})", carta.valor_str(), regla),
+        None => format!("Banca ({})", regla),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Magenta))
+        .title_top(Span::styled(titulo, Style::default().fg(Color::White)));
+
+    let (Some(_), Some((_, mut resultados))) = (carta_visible, app.finales_banca) else {
+        let aviso = Paragraph::new("Sin carta visible")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block)
+            .centered();
+        frame.render_widget(aviso, area);
+        return;
+    };
+
+    // Tras el peek sin blackjack solo quedan los demás finales, también en el turno de la
+    // banca y al acabar la ronda si la oculta sigue sin verse
+    let tras_peek = matches!(
+        app.estado,
+        GameState::TurnoJugador | GameState::TurnoBanca | GameState::FinJuego
+    );
+    if !reglas.peek.sin_carta_oculta() && tras_peek && !banca.mano().es_blackjack() {
+        resultados[6] = 0.0;
+        let suma: f64 = resultados.iter().sum();
+        if suma > 0.0 {
+            resultados.iter_mut().for_each(|p| *p /= suma);
+        }
+    }
+
+    let nombres = ["17", "18", "19", "20", "21", "Se pasa", "Blackjack"];
+    let filas = nombres.iter().zip(resultados).map(|(nombre, p)| {
+        let fila = Row::new(vec![
+            nombre.to_string(),
+            format!("{:5.1}%", p * 100.0),
+            "█".repeat((p * 12.0).round() as usize),
+        ]);
+        if *nombre == "Se pasa" {
+            fila.style(Style::default().fg(Color::Green))
+        } else {
+            fila
+        }
+    });
+    let tabla = Table::new(
+        filas,
+        [
+            Constraint::Length(9),
+            Constraint::Length(6),
+            Constraint::Min(1),
+        ],
+    )
+    .style(Style::default().fg(Color::White))
+    .block(block);
+    frame.render_widget(tabla, area);
+}
+
+// Selector de perfil con las estadísticas de siempre del perfil marcado
+fn render_perfiles(frame: &mut ratatui::Frame, area: ratatui::layout::Rect, app: &AppState) {
+    let columnas = Layout::horizontal([Constraint::Min(10), Constraint::Min(10)])
+        .vertical_margin(2)
+        .spacing(3)
+        .split(area);
+
+    let continuar = usize::from(app.partida.is_some());
+    let nombres = app
+        .partida
+        .iter()
+        .map(|_| "▶ Continuar partida")
+        .chain(
+            app.perfiles
+                .perfiles
+                .iter()
+                .map(|perfil| perfil.nombre.as_str()),
+        )
+        .chain(["+ Nuevo perfil", "Jugar sin perfil"]);
+    let filas: Vec<Row> = nombres
+        .enumerate()
+        .map(|(i, nombre)| {
+            let fila = Row::new(vec![nombre.to_string()]);
+            if i == app.seleccion {
+                fila.style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                fila
+            }
+        })
+        .collect();
+    let lista = Table::new(filas, [Constraint::Min(10)])
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Blue))
+                .title_top(Span::styled("Perfiles", Style::default().fg(Color::White))),
+        );
+    frame.render_widget(lista, columnas[0]);
+
+    let continua = app.seleccion < continuar;
+    let bloque = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Green))
+        .title_top(Span::styled(
+            if continua {
+                "Continuar partida"
+            } else {
+                "Estadísticas de siempre"
+            },
+            Style::default().fg(Color::White),
+        ));
+    if let Some(resumen) = app.partida.as_ref().filter(|_| continua) {
+        let aviso = Paragraph::new(vec![
+            Line::from("Partida guardada al salir:"),
+            Line::from(""),
+            Line::from(Span::styled(
+                resumen.as_str(),
+                Style::default().fg(Color::Yellow),
+            )),
+        ])
+        .style(Style::default().fg(Color::White))
+        .block(bloque)
+        .centered();
+        frame.render_widget(aviso, columnas[1]);
+        return;
+    }
+    let perfil = app.seleccion.checked_sub(continuar);
+    let Some(perfil) = perfil.and_then(|perfil| app.perfiles.perfiles.get(perfil)) else {
+        let aviso = Paragraph::new("Las partidas sin perfil no se guardan en las estadísticas")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(bloque)
+            .centered();
+        frame.render_widget(aviso, columnas[1]);
+        return;
+    };
+    let estadisticas = &perfil.estadisticas;
+    let racha = match estadisticas.racha {
+        0 => "-".to_string(),
+        r if r > 0 => format!("{} ganando", r),
+        r => format!("{} perdiendo", -r),
+    };
+    let datos = [
+        ("Rondas", estadisticas.rondas.to_string()),
+        ("Manos", estadisticas.manos.to_string()),
+        (
+            "Ganadas",
+            format!(
+                "{} ({:.1}%)",
+                estadisticas.ganadas,
+                estadisticas.tasa_victorias() * 100.0
+            ),
+        ),
+        ("Perdidas", estadisticas.perdidas.to_string()),
+        ("Empates", estadisticas.empates.to_string()),
+        ("Blackjacks", estadisticas.blackjacks.to_string()),
+        ("Pasadas", estadisticas.pasadas.to_string()),
+        (
+            "Dobladas ganadas",
+            estadisticas.dobladas_ganadas.to_string(),
+        ),
+        ("Saldo neto", format!("{:+}", estadisticas.neto)),
+        ("Racha actual", racha),
+        ("Mejor racha", estadisticas.mejor_racha.to_string()),
+        ("Peor racha", estadisticas.peor_racha.to_string()),
+    ];
+    let tabla = Table::new(
+        datos
+            .into_iter()
+            .map(|(nombre, valor)| Row::new(vec![nombre.to_string(), valor])),
+        [Constraint::Length(18), Constraint::Min(10)],
+    )
+    .style(Style::default().fg(Color::White))
+    .block(bloque);
+    frame.render_widget(tabla, columnas[1]);
+}
+
+// Informe de decisiones frente a la estrategia básica, por categoría de mano
+fn render_informe(
+    frame: &mut ratatui::Frame,
+    area: ratatui::layout::Rect,
+    registro: &RegistroDecisiones,
+) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title_top(Span::styled(
+            "Informe de estrategia básica",
+            Style::default().fg(Color::White),
+        ));
+    let interior = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([Constraint::Length(6), Constraint::Min(1)])
+        .margin(1)
+        .split(interior);
+
+    let fila = |nombre: &str, resumen: crate::game::mistakes::Resumen| {
+        Row::new(vec![
+            nombre.to_string(),
+            resumen.decisiones.to_string(),
+            resumen.errores.to_string(),
+            format!("{:.1}%", resumen.tasa_error() * 100.0),
+            format!("{:.3}", resumen.coste),
+        ])
+    };
+    let mut filas: Vec<Row> = Categoria::TODAS
+        .iter()
+        .map(|categoria| fila(categoria.nombre(), registro.resumen(Some(*categoria))))
+        .collect();
+    filas.push(
+        fila("Total", registro.resumen(None)).style(Style::default().add_modifier(Modifier::BOLD)),
+    );
+
+    let tabla = Table::new(
+        filas,
+        [
+            Constraint::Length(16),
+            Constraint::Length(11),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(14),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Categoría",
+            "Decisiones",
+            "Errores",
+            "% error",
+            "Coste (apuestas)",
+        ])
+        .style(Style::default().fg(Color::Yellow)),
+    )
+    .style(Style::default().fg(Color::White));
+    frame.render_widget(tabla, chunks[0]);
+
+    // Los errores más recientes primero
+    let errores: Vec<String> = registro
+        .errores()
+        .rev()
+        .map(|error| {
+            format!(
+                "{}: {} en vez de {} (-{:.3})",
+                error.descripcion(),
+                error.tomada.nombre(),
+                error.correcta.nombre(),
+                error.coste
+            )
+        })
+        .collect();
+    let texto = if errores.is_empty() {
+        "Sin errores. ¡Estrategia perfecta!".to_string()
+    } else {
+        errores.join("\n")
+    };
+    let lista = Paragraph::new(texto).style(Style::default().fg(Color::Gray));
+    frame.render_widget(lista, chunks[1]);
+}
+
+// Panel con la evolución del saldo y los resultados del historial agrupados
+fn render_estadisticas(
+    frame: &mut ratatui::Frame,
+    area: ratatui::layout::Rect,
+    tendencias: &Tendencias,
+) {
+    let filas =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).split(area);
+    let arriba = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(filas[0]);
+    let derecha =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).split(arriba[1]);
+    let abajo = Layout::horizontal([
+        Constraint::Percentage(28),
+        Constraint::Percentage(47),
+        Constraint::Percentage(25),
+    ])
+    .split(filas[1]);
+
+    let marco = |titulo: String, color: Color| {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(color))
+            .title_top(Span::styled(titulo, Style::default().fg(Color::White)))
+    };
+
+    // Ganancia acumulada de todas las rondas del historial
+    let (mut minimo, mut maximo) = tendencias
+        .acumulado
+        .iter()
+        .fold((0.0, 0.0), |(minimo, maximo), &(_, neto)| {
+            (f64::min(minimo, neto), f64::max(maximo, neto))
+        });
+    if maximo - minimo < 2.0 {
+        minimo -= 1.0;
+        maximo += 1.0;
+    }
+    let rondas = tendencias.acumulado.len() as f64;
+    let final_neto = tendencias.total.neto;
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(if final_neto >= 0 {
+            Color::Green
+        } else {
+            Color::Red
+        }))
+        .data(&tendencias.acumulado);
+    let grafica = Chart::new(vec![dataset])
+        .block(marco(
+            format!("Ganancia acumulada: {:+}", final_neto),
+            Color::Blue,
+        ))
+        .x_axis(
+            Axis::default()
+                .title("Ronda")
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([1.0, rondas.max(2.0)])
+                .labels(vec!["1".into(), format!("{}", rondas).into()]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([minimo, maximo])
+                .labels(vec![
+                    format!("{:+.0}", minimo).into(),
+                    format!("{:+.0}", (minimo + maximo) / 2.0).into(),
+                    format!("{:+.0}", maximo).into(),
+                ]),
+        );
+    frame.render_widget(grafica, arriba[0]);
+
+    // Saldo de la última sesión, con las últimas rondas que caben
+    let saldo = &tendencias.saldo_sesion;
+    let bajo = saldo.iter().copied().min().unwrap_or(0);
+    let alto = saldo.iter().copied().max().unwrap_or(0);
+    let caben = derecha[0].width.saturating_sub(2) as usize;
+    let datos: Vec<u64> = saldo[saldo.len().saturating_sub(caben)..]
+        .iter()
+        // Se dibuja desde el saldo más bajo para que se noten las variaciones
+        .map(|valor| valor - bajo + 1)
+        .collect();
+    let sesion = Sparkline::default()
+        .block(marco(
+            format!("Saldo en la última sesión ({} - {})", bajo, alto),
+            Color::Blue,
+        ))
+        .style(Style::default().fg(Color::Yellow))
+        .data(&datos);
+    frame.render_widget(sesion, derecha[0]);
+
+    // Manos ganadas, empatadas y perdidas
+    let total = &tendencias.total;
+    let porcentaje = |manos: u32| {
+        if total.manos == 0 {
+            0.0
+        } else {
+            manos as f64 * 100.0 / total.manos as f64
+        }
+    };
+    let barras: Vec<Bar> = [
+        ("Ganadas", total.ganadas, Color::Green),
+        ("Empates", total.empates, Color::Yellow),
+        ("Perdidas", total.perdidas, Color::Red),
+    ]
+    .into_iter()
+    .map(|(nombre, manos, color)| {
+        Bar::default()
+            .label(nombre.into())
+            .value(manos as u64)
+            .text_value(format!("{} ({:.0}%)", manos, porcentaje(manos)))
+            .style(Style::default().fg(color))
+            .value_style(Style::default().fg(Color::Black).bg(color))
+    })
+    .collect();
+    let reparto = BarChart::default()
+        .block(marco(format!("{} manos", total.manos), Color::Blue))
+        .data(BarGroup::default().bars(&barras))
+        .bar_width(ancho_barras(derecha[1], barras.len()))
+        .bar_gap(1);
+    frame.render_widget(reparto, derecha[1]);
+
+    // Porcentaje de manos ganadas según la carta de la banca y el total inicial
+    let por_carta: Vec<Bar> = tendencias
+        .por_carta_banca
+        .iter()
+        .enumerate()
+        .map(|(i, grupo)| {
+            let etiqueta = if i == 9 {
+                "A".to_string()
+            } else {
+                (i + 2).to_string()
+            };
+            barra_victorias(etiqueta, grupo)
+        })
+        .collect();
+    let carta = BarChart::default()
+        .block(marco(
+            "% ganadas por carta de la banca".to_string(),
+            Color::Red,
+        ))
+        .data(BarGroup::default().bars(&por_carta))
+        .max(100)
+        .bar_width(ancho_barras(abajo[0], por_carta.len()))
+        .bar_gap(1);
+    frame.render_widget(carta, abajo[0]);
+
+    let por_total: Vec<Bar> = tendencias
+        .por_total
+        .iter()
+        .enumerate()
+        .map(|(i, grupo)| barra_victorias((i as u8 + TOTAL_MINIMO).to_string(), grupo))
+        .collect();
+    let inicial = BarChart::default()
+        .block(marco(
+            "% ganadas por total inicial".to_string(),
+            Color::Blue,
+        ))
+        .data(BarGroup::default().bars(&por_total))
+        .max(100)
+        .bar_width(ancho_barras(abajo[1], por_total.len()))
+        .bar_gap(1);
+    frame.render_widget(inicial, abajo[1]);
+
+    // Resultados de cada mesa en la que se ha jugado
+    let filas_mesa: Vec<Row> = tendencias
+        .por_mesa
+        .iter()
+        .map(|(mesa, grupo)| {
+            let estilo = Style::default().fg(if grupo.neto >= 0 {
+                Color::Green
+            } else {
+                Color::Red
+            });
+            Row::new(vec![
+                mesa.clone(),
+                grupo.rondas.to_string(),
+                format!("{:.0}%", grupo.tasa_victorias() * 100.0),
+                format!("{:+}", grupo.neto),
+            ])
+            .style(estilo)
+        })
+        .collect();
+    let mesas = Table::new(
+        filas_mesa,
+        [
+            Constraint::Min(10),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(7),
+        ],
+    )
+    .header(
+        Row::new(vec!["Mesa", "Rondas", "% gan", "Neto"]).style(Style::default().fg(Color::Yellow)),
+    )
+    .block(marco("Por mesa".to_string(), Color::Green));
+    frame.render_widget(mesas, abajo[2]);
+}
+
+// Barra con el porcentaje de manos ganadas de un grupo, verde si gana más de lo que pierde
+fn barra_victorias(etiqueta: String, grupo: &EstadisticasPerfil) -> Bar<'static> {
+    let porcentaje = (grupo.tasa_victorias() * 100.0).round() as u64;
+    let color = if grupo.ganadas >= grupo.perdidas {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    let texto = if grupo.manos == 0 {
+        "-".to_string()
+    } else {
+        porcentaje.to_string()
+    };
+    Bar::default()
+        .label(etiqueta.into())
+        .value(porcentaje)
+        .text_value(texto)
+        .style(Style::default().fg(color))
+        .value_style(Style::default().fg(Color::Black).bg(color))
+}
+
+// Ancho de cada barra para que quepan todas dentro del marco, con un hueco entre ellas
+fn ancho_barras(area: ratatui::layout::Rect, barras: usize) -> u16 {
+    ((area.width.saturating_sub(2) + 1) / barras as u16)
+        .saturating_sub(1)
+        .max(1)
+}
+
+// Color de cada jugada en la tabla de estrategia básica
+fn color_accion(accion: Accion) -> Color {
+    match accion {
+        Accion::Pedir => Color::Red,
+        Accion::Plantarse => Color::Yellow,
+        Accion::Doblar => Color::Green,
+        Accion::Dividir => Color::Cyan,
+        Accion::Rendirse => Color::Magenta,
+    }
+}
+
+// Tablas de estrategia básica calculadas con las reglas de la mesa y el EV de la casilla elegida
+fn render_tabla_estrategia(
+    frame: &mut ratatui::Frame,
+    area: ratatui::layout::Rect,
+    cursor: CursorTabla,
+    reglas: &ReglasMesa,
+) {
+    let filas = Layout::vertical([Constraint::Length(19), Constraint::Min(4)]).split(area);
+    let tablas = Layout::horizontal([
+        Constraint::Ratio(1, 3),
+        Constraint::Ratio(1, 3),
+        Constraint::Ratio(1, 3),
+    ])
+    .split(filas[0]);
+
+    let carta = |valor: u8| match valor {
+        11 => "A".to_string(),
+        valor => valor.to_string(),
+    };
+    for (t, categoria) in Categoria::TODAS.iter().enumerate() {
+        let elegida = cursor.tabla == t;
+        let cabecera = Row::new(std::iter::once(Cell::from("")).chain(
+            CARTAS_BANCA.iter().enumerate().map(|(c, &banca)| {
+                let estilo = if elegida && cursor.columna == c {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Cell::from(format!(" {}", carta(banca))).style(estilo)
+            }),
+        ));
+        let filas_tabla: Vec<Row> = chart::filas(*categoria)
+            .into_iter()
+            .enumerate()
+            .map(|(f, fila)| {
+                let estilo_fila = if elegida && cursor.fila == f {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                let nombre = Cell::from(chart::nombre_fila(*categoria, fila)).style(estilo_fila);
+                Row::new(
+                    std::iter::once(nombre).chain(CARTAS_BANCA.iter().enumerate().map(
+                        |(c, &banca)| {
+                            let situacion = chart::situacion(*categoria, fila, banca);
+                            let casilla = Casilla::nueva(&situacion, reglas);
+                            let mut estilo = Style::default()
+                                .fg(Color::Black)
+                                .bg(color_accion(casilla.accion));
+                            if elegida && cursor.fila == f && cursor.columna == c {
+                                estilo = estilo.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+                            }
+                            Cell::from(format!(" {:<3}", casilla.texto())).style(estilo)
+                        },
+                    )),
+                )
+            })
+            .collect();
+        let anchos = std::iter::once(Constraint::Length(6))
+            .chain(CARTAS_BANCA.iter().map(|_| Constraint::Length(4)));
+        let tabla = Table::new(filas_tabla, anchos)
+            .header(cabecera)
+            .column_spacing(0)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(if elegida {
+                        Color::Yellow
+                    } else {
+                        Color::Blue
+                    }))
+                    .title_top(Span::styled(
+                        categoria.nombre(),
+                        Style::default().fg(Color::White),
+                    )),
+            );
+        frame.render_widget(tabla, tablas[t]);
+    }
+
+    // Valor esperado de cada jugada en la casilla elegida
+    let situacion = cursor.situacion();
+    let casilla = Casilla::nueva(&situacion, reglas);
+    let mut lineas = vec![Line::from(vec![
+        Span::raw("Estrategia básica: "),
+        Span::styled(
+            casilla.accion.nombre(),
+            Style::default()
+                .fg(color_accion(casilla.accion))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(match casilla.alternativa {
+            Some(alternativa) => format!(" (si no se puede: {})", alternativa.nombre()),
+            None => String::new(),
+        }),
+    ])];
+    lineas.push(Line::from(""));
+    lineas.extend(
+        chart::jugadas(&situacion, reglas)
+            .into_iter()
+            .map(|(accion, ev)| {
+                let estilo = Style::default().fg(if accion == casilla.accion {
+                    color_accion(accion)
+                } else {
+                    Color::Gray
+                });
+                Line::from(Span::styled(
+                    format!("{:<12} {:+.4}", accion.nombre(), ev),
+                    estilo,
+                ))
+            }),
+    );
+    let ev = Paragraph::new(lineas).centered().block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Green))
+            .title_top(Span::styled(
+                format!(
+                    "{} · EV por apuesta inicial con shoe infinito",
+                    situacion.descripcion()
+                ),
+                Style::default().fg(Color::White),
+            )),
+    );
+    frame.render_widget(ev, filas[1]);
+}
+
+// Aciertos de la sesión de práctica y situaciones que más se fallan
+fn render_practica(
+    frame: &mut ratatui::Frame,
+    area: ratatui::layout::Rect,
+    practica: &Entrenamiento,
+) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Green))
+        .title_top(Span::styled("Práctica", Style::default().fg(Color::White)));
+    let interior = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([Constraint::Length(4), Constraint::Min(1)])
+        .margin(1)
+        .split(interior);
+
+    let sesion = &practica.sesion;
+    let resumen = Paragraph::new(vec![
+        Line::from(format!(
+            "Esta sesión: {}/{} ({:.0}%)",
+            sesion.aciertos,
+            sesion.intentos,
+            sesion.precision() * 100.0
+        )),
+        Line::from(format!(
+            "Tiempo medio: {:.1} s por respuesta",
+            sesion.tiempo_medio()
+        )),
+        Line::from(if practica.repaso {
+            "Repaso de puntos débiles: sí"
+        } else {
+            "Repaso de puntos débiles: no"
+        }),
+    ])
+    .style(Style::default().fg(Color::White));
+    frame.render_widget(resumen, chunks[0]);
+
+    let filas: Vec<Row> = practica
+        .puntos
+        .peores(chunks[1].height.saturating_sub(1) as usize)
+        .into_iter()
+        .map(|(situacion, marca)| {
+            Row::new(vec![
+                situacion.to_string(),
+                format!("{}/{}", marca.aciertos, marca.intentos),
+                format!("{:.0}%", marca.precision() * 100.0),
+                format!("{:.1} s", marca.tiempo_medio()),
+            ])
+        })
+        .collect();
+    let tabla = Table::new(
+        filas,
+        [
+            Constraint::Length(17),
+            Constraint::Length(8),
+            Constraint::Length(5),
+            Constraint::Length(7),
+        ],
+    )
+    .header(
+        Row::new(vec!["Puntos débiles", "Aciertos", "%", "Tiempo"])
+            .style(Style::default().fg(Color::Yellow)),
+    )
+    .style(Style::default().fg(Color::Gray));
+    frame.render_widget(tabla, chunks[1]);
 }